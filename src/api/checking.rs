@@ -19,15 +19,17 @@ pub struct GuidanceProfile {
 #[allow(non_snake_case)]
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Language {
-    displayName: String,
+    #[serde(default)]
+    pub id: String,
+    pub displayName: String,
 }
 
 #[allow(non_snake_case)]
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Goal {
-    id: GoalId,
-    displayName: String,
-    color: String,
+    pub id: GoalId,
+    pub displayName: String,
+    pub color: String,
 }
 
 #[allow(non_snake_case)]
@@ -55,19 +57,20 @@ pub struct ContentFormat {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum ContentEncoding {
     base64,
     none,
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum CheckType {
     batch,
     interactive,
     baseline,
-    automated
+    automated,
+    partial
 }
 
 #[allow(non_camel_case_types)]
@@ -86,6 +89,7 @@ pub struct CheckingCapabilitiesLinks {}
 #[derive(Serialize, Debug, Default)]
 pub struct CheckRequest {
     pub content: String,
+    pub contentEncoding: Option<ContentEncoding>,
     pub document: Option<DocumentInfo>,
     pub checkOptions: CheckOptions,
 }
@@ -96,7 +100,18 @@ pub struct CheckRequest {
 pub struct CheckOptions {
     pub guidanceProfileId: Option<String>,
     pub batchId: Option<String>,
-    pub disableCustomFieldValidation: Option<bool>
+    pub disableCustomFieldValidation: Option<bool>,
+    pub contentFormat: Option<ContentFormatId>,
+    pub checkType: Option<CheckType>,
+    /// Partial checks only report the issues and the score of these ranges.
+    pub partialCheckRanges: Option<Vec<OffsetRange>>,
+}
+
+/// The offsets count UTF-16 code units of the content.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct OffsetRange {
+    pub begin: usize,
+    pub end: usize,
 }
 
 
@@ -129,10 +144,150 @@ pub struct CancelCheckResponseData {
 }
 
 #[allow(non_snake_case)]
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct CheckResult {
-    //pub id: u64,
-    pub quality: CheckResultQuality
+    #[serde(default)]
+    pub id: Option<CheckId>,
+    pub quality: CheckResultQuality,
+    #[serde(default)]
+    pub counts: Option<CheckResultCounts>,
+    #[serde(default)]
+    pub goals: Vec<CheckResultGoal>,
+    #[serde(default)]
+    pub issues: Vec<Issue>,
+    #[serde(default)]
+    pub keywords: Option<Keywords>,
+    #[serde(default)]
+    pub reports: CheckResultReports,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct CheckResultCounts {
+    #[serde(default)]
+    pub sentences: usize,
+    #[serde(default)]
+    pub words: usize,
+    #[serde(default)]
+    pub issues: usize,
+}
+
+/// A goal of the used guidance profile together with its number of issues.
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CheckResultGoal {
+    #[serde(flatten)]
+    pub goal: Goal,
+    #[serde(default)]
+    pub issues: usize,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Issue {
+    pub goalId: GoalId,
+    #[serde(default)]
+    pub internalName: Option<String>,
+    #[serde(default)]
+    pub displayNameHtml: Option<String>,
+    #[serde(default)]
+    pub guidanceHtml: Option<String>,
+    pub displaySurface: String,
+    #[serde(default)]
+    pub issueType: Option<String>,
+    pub positionalInformation: PositionalInformation,
+    #[serde(default)]
+    pub readOnly: bool,
+    #[serde(default)]
+    pub suggestions: Vec<Suggestion>,
+    #[serde(default)]
+    pub canAddToDictionary: bool,
+    #[serde(default)]
+    pub links: IssueLinks,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug)]
+pub struct PositionalInformation {
+    #[serde(default)]
+    pub matches: Vec<Match>,
+}
+
+/// The original offsets refer to the content of the `CheckRequest`,
+/// the extracted offsets to the text which has been extracted from it by the server.
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Match {
+    #[serde(default)]
+    pub extractedPart: Option<String>,
+    #[serde(default)]
+    pub extractedBegin: Option<usize>,
+    #[serde(default)]
+    pub extractedEnd: Option<usize>,
+    pub originalPart: String,
+    pub originalBegin: usize,
+    pub originalEnd: usize,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Suggestion {
+    pub surface: String,
+    #[serde(default)]
+    pub groupId: Option<String>,
+    /// One replacement per match of the issue. `None` keeps the text of the match.
+    #[serde(default)]
+    pub replacements: Vec<Option<String>>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct IssueLinks {
+    #[serde(default)]
+    pub help: Option<String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Keywords {
+    #[serde(default)]
+    pub discovered: Vec<Keyword>,
+    #[serde(default)]
+    pub target: Vec<Keyword>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Keyword {
+    pub keyword: String,
+    #[serde(default)]
+    pub sortKey: Option<String>,
+    #[serde(default)]
+    pub density: Option<f64>,
+    #[serde(default)]
+    pub count: Option<usize>,
+    #[serde(default)]
+    pub prominence: Option<f64>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct CheckResultReports {
+    #[serde(default)]
+    pub scorecard: Option<Report>,
+    #[serde(default)]
+    pub extractedText: Option<Report>,
+    #[serde(default)]
+    pub termHarvesting: Option<Report>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Report {
+    pub displayName: String,
+    pub link: String,
+    #[serde(default)]
+    pub linkAuthenticated: Option<String>,
 }
 
 #[allow(non_snake_case)]
@@ -140,7 +295,7 @@ pub struct CheckResult {
 pub struct CheckResultLinks {}
 
 #[allow(non_snake_case)]
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct CheckResultQuality {
     pub score: f64,
     pub status: QualityStatus,
@@ -162,7 +317,7 @@ pub struct AggregatedReportLinkResponse {
 }
 
 #[allow(non_snake_case)]
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct AggregatedReportLink {
     pub reportType: AggregatedReportType,
     pub link: String,
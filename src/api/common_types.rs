@@ -1,5 +1,5 @@
 use serde_derive::{Deserialize};
-use crate::api::errors::ApiError;
+use crate::api::errors::ServerProblem;
 
 pub type Locale = String;
 
@@ -19,7 +19,7 @@ pub struct NoLinks {
 #[allow(non_snake_case)]
 #[derive(Deserialize, Debug)]
 pub struct ErrorResponse {
-    pub error: ApiError
+    pub error: ServerProblem
 }
 
 
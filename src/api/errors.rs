@@ -1,61 +1,152 @@
 use std::convert::From;
 use std::error::Error;
+use std::fmt;
+use std::io;
 
-use lazy_static::lazy_static;
 use reqwest;
-use serde_derive::Deserialize;
+use serde::Serializer;
+use serde_derive::{Deserialize, Serialize};
 
 pub static CHECK_CANCELLED_ERROR_TYPE: &str = "checkCancelled";
+pub static NETWORK_ERROR_TYPE: &str = "network";
+pub static TIMEOUT_ERROR_TYPE: &str = "timeout";
+pub static DECODE_ERROR_TYPE: &str = "decode";
+pub static IO_ERROR_TYPE: &str = "io";
+pub static ENCODING_ERROR_TYPE: &str = "encoding";
+pub static UNSUPPORTED_ERROR_TYPE: &str = "unsupported";
 
-lazy_static! {
-    pub static ref CHECK_CANCELLED_ERROR: ApiError = ApiError {
-        _type: CHECK_CANCELLED_ERROR_TYPE.to_string(),
-        title: "Check cancelled".to_string(),
-        detail: "".to_string(),
-        status: None,
-    };
-}
-
+const HTTP_STATUS_UNAUTHORIZED: u16 = 401;
+const AUTH_PROBLEM_TYPE: &str = "auth";
 
+/// The problem document, which the server returns for failed requests.
 #[allow(non_snake_case)]
-#[derive(Deserialize, Debug, Clone)]
-pub struct ApiError {
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ServerProblem {
     #[serde(rename = "type")]
     pub _type: String,
     pub title: String,
+    #[serde(default)]
     pub detail: String,
     pub status: Option<u16>,
 }
 
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    /// The server has rejected the request.
+    Server(ServerProblem),
+    /// The access token is invalid or has expired.
+    AuthExpired(ServerProblem),
+    /// The server could not be reached or the connection broke.
+    Network(String),
+    Timeout(String),
+    /// The response has an unexpected format.
+    Decode(String),
+    Io(String),
+    /// A text file doesn't match its detected or configured encoding.
+    Encoding(String),
+    /// The server can't check this kind of document.
+    Unsupported(String),
+    Cancelled,
+}
+
+impl ApiError {
+    pub fn error_type(&self) -> &str {
+        match self {
+            ApiError::Server(problem) | ApiError::AuthExpired(problem) => &problem._type,
+            ApiError::Network(_) => NETWORK_ERROR_TYPE,
+            ApiError::Timeout(_) => TIMEOUT_ERROR_TYPE,
+            ApiError::Decode(_) => DECODE_ERROR_TYPE,
+            ApiError::Io(_) => IO_ERROR_TYPE,
+            ApiError::Encoding(_) => ENCODING_ERROR_TYPE,
+            ApiError::Unsupported(_) => UNSUPPORTED_ERROR_TYPE,
+            ApiError::Cancelled => CHECK_CANCELLED_ERROR_TYPE,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            ApiError::Server(problem) | ApiError::AuthExpired(problem) => &problem.title,
+            ApiError::Network(_) => "Network error",
+            ApiError::Timeout(_) => "Timeout",
+            ApiError::Decode(_) => "Unexpected response",
+            ApiError::Io(_) => "IO error",
+            ApiError::Encoding(_) => "Invalid encoding",
+            ApiError::Unsupported(_) => "Unsupported document",
+            ApiError::Cancelled => "Check cancelled",
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            ApiError::Server(problem) | ApiError::AuthExpired(problem) => &problem.detail,
+            ApiError::Network(detail) | ApiError::Timeout(detail) | ApiError::Decode(detail) |
+            ApiError::Io(detail) | ApiError::Encoding(detail) | ApiError::Unsupported(detail) => detail,
+            ApiError::Cancelled => "",
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ApiError::Server(problem) | ApiError::AuthExpired(problem) => problem.status,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.detail().is_empty() {
+            write!(f, "{}", self.title())
+        } else {
+            write!(f, "{}: {}", self.title(), self.detail())
+        }
+    }
+}
+
+impl Error for ApiError {}
+
+/// Serializes all variants like a problem document of the server.
+impl serde::Serialize for ApiError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ServerProblem {
+            _type: self.error_type().to_string(),
+            title: self.title().to_string(),
+            detail: self.detail().to_string(),
+            status: self.status(),
+        }.serialize(serializer)
+    }
+}
+
+impl From<ServerProblem> for ApiError {
+    fn from(problem: ServerProblem) -> ApiError {
+        if problem.status == Some(HTTP_STATUS_UNAUTHORIZED) || problem._type == AUTH_PROBLEM_TYPE {
+            ApiError::AuthExpired(problem)
+        } else {
+            ApiError::Server(problem)
+        }
+    }
+}
+
 impl From<reqwest::Error> for ApiError {
     fn from(request_error: reqwest::Error) -> ApiError {
-        ApiError {
-            _type: "RequestError".to_string(),
-            title: request_error.description().to_string(),
-            detail: request_error.to_string(),
-            status: request_error.status().map(|s| s.as_u16()),
+        if request_error.is_timeout() {
+            ApiError::Timeout(request_error.to_string())
+        } else if request_error.is_serialization() {
+            ApiError::Decode(request_error.to_string())
+        } else {
+            ApiError::Network(request_error.to_string())
         }
     }
 }
 
 impl From<serde_json::error::Error> for ApiError {
     fn from(serde_error: serde_json::error::Error) -> ApiError {
-        ApiError {
-            _type: "SerdeError".to_string(),
-            title: serde_error.description().to_string(),
-            detail: serde_error.to_string(),
-            status: None,
-        }
+        ApiError::Decode(serde_error.to_string())
     }
 }
 
-impl From<std::io::Error> for ApiError {
-    fn from(io_error: std::io::Error) -> ApiError {
-        ApiError {
-            _type: "IoError".to_string(),
-            title: io_error.description().to_string(),
-            detail: io_error.to_string(),
-            status: None,
-        }
+impl From<io::Error> for ApiError {
+    fn from(io_error: io::Error) -> ApiError {
+        ApiError::Io(io_error.to_string())
     }
 }
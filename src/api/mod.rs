@@ -1,5 +1,4 @@
 use reqwest;
-use reqwest::Error;
 use reqwest::header::{CONTENT_TYPE, USER_AGENT};
 use mime::APPLICATION_JSON;
 use serde;
@@ -14,17 +13,19 @@ pub mod errors;
 pub mod server_info;
 pub mod signin;
 pub mod common_types;
+pub mod retry;
 
 use self::checking::*;
 use self::server_info::*;
 use self::signin::*;
-use self::errors::ApiError;
+use self::errors::{ApiError, ServerProblem};
 use crate::api::common_types::SuccessResponse;
 use crate::api::common_types::ApiPollResponse;
 use hyper::HeaderMap;
 use crate::api::common_types::ErrorResponse;
 use log::info;
 use crate::api::common_types::NoLinks;
+use crate::api::retry::{RetryPolicy, is_retryable_error, is_retryable_status, retry_after};
 
 const HEADER_ACROLINX_CLIENT_LOCALE: &str = "X-Acrolinx-Client-Locale";
 const HEADER_ACROLINX_AUTH: &str = "X-Acrolinx-Auth";
@@ -34,12 +35,34 @@ const HEADER_ACROLINX_CLIENT: &str = "X-Acrolinx-Client";
 pub struct AcroApi {
     props: AcroApiProps,
     authentication: Option<String>,
+    http_client: reqwest::Client,
 }
 
 pub struct AcroApiProps {
     pub server_url: String,
     pub locale: String,
     pub client: ClientInformation,
+    pub http: HttpOptions,
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpOptions {
+    pub connect_timeout: Duration,
+    /// Limits the whole request including reading the response.
+    pub request_timeout: Duration,
+    pub max_idle_connections_per_host: usize,
+    pub retry: RetryPolicy,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        HttpOptions {
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(60),
+            max_idle_connections_per_host: 8,
+            retry: RetryPolicy::default(),
+        }
+    }
 }
 
 pub struct ClientInformation {
@@ -51,8 +74,13 @@ pub struct ClientInformation {
 const API_BASE_PATH: &str = "/api/v1";
 
 impl AcroApi {
-    pub fn new<S: Into<String>>(props: AcroApiProps, authentication: Option<S>) -> Self {
-        AcroApi { props, authentication: authentication.map(Into::into) }
+    pub fn new<S: Into<String>>(props: AcroApiProps, authentication: Option<S>) -> Result<Self, ApiError> {
+        let http_client = reqwest::Client::builder()
+            .connect_timeout(props.http.connect_timeout)
+            .timeout(props.http.request_timeout)
+            .max_idle_per_host(props.http.max_idle_connections_per_host)
+            .build()?;
+        Ok(AcroApi { props, authentication: authentication.map(Into::into), http_client })
     }
 
     pub fn set_access_token(&mut self, access_token: &str) {
@@ -63,10 +91,10 @@ impl AcroApi {
         self.get_data("")
     }
 
-    pub fn signin(&self) -> Result<SigninRequestResponse, Error> {
+    pub fn signin(&self) -> Result<SigninRequestResponse, ApiError> {
         let url = self.props.server_url.clone() + "/api/v1/auth/sign-ins";
         let body = SigninRequest {};
-        self.post(&url, &body)?.json()
+        check_status(self.post(&url, &body)?)?.json().map_err(ApiError::from)
     }
 
     pub fn get_checking_capabilities(&self) -> Result<CheckingCapabilities, ApiError> {
@@ -76,12 +104,12 @@ impl AcroApi {
     pub fn check(&self, check_request: &CheckRequest)
                  -> Result<SuccessResponse<CheckResponse, CheckResponseLinks>, ApiError> {
         let url = self.props.server_url.clone() + "/api/v1/checking/checks";
-        self.post(&url, &check_request)?.json().map_err(ApiError::from)
+        check_status(self.post(&url, &check_request)?)?.json().map_err(ApiError::from)
     }
 
     pub fn cancel_check(&self, check_response_links: &CheckResponseLinks)
                         -> Result<SuccessResponse<CancelCheckResponseData, NoLinks>, ApiError> {
-        self.delete(&check_response_links.cancel)?.json().map_err(ApiError::from)
+        check_status(self.delete(&check_response_links.cancel)?)?.json().map_err(ApiError::from)
     }
 
     pub fn get_checking_result(&self, check_response_links: &CheckResponseLinks)
@@ -105,74 +133,100 @@ impl AcroApi {
     pub fn wait_for_signin(&self, signin_links: &SigninLinks) -> Result<LoggedInResponse, ApiError> {
         let mut res = self.poll_for_signin(signin_links, None)?;
 
-        while let PollInteractiveSigninResponse::PollMoreResult(poll_more) = res {
-            info!("Polling ");
-            res = self.poll_for_signin(signin_links, Some(&poll_more))?;
-        }
-
-        if let PollInteractiveSigninResponse::LoggedIn(signed_in) = res {
-            Ok(signed_in)
-        } else {
-            panic!("This should never happen.");
+        loop {
+            match res {
+                PollInteractiveSigninResponse::PollMoreResult(poll_more) => {
+                    info!("Polling ");
+                    res = self.poll_for_signin(signin_links, Some(&poll_more))?;
+                }
+                PollInteractiveSigninResponse::LoggedIn(signed_in) => return Ok(signed_in),
+            }
         }
     }
 
-    fn get_raw<U: reqwest::IntoUrl>(&self, url: U) -> reqwest::Result<reqwest::Response> {
-        reqwest::Client::new()
-            .get(url)
-            .headers(self.create_common_headers())
-            .send()
+    fn get_raw(&self, url: &str) -> reqwest::Result<reqwest::Response> {
+        self.send_with_retry(true, || {
+            self.http_client
+                .get(url)
+                .headers(self.create_common_headers())
+        })
     }
 
-    fn get<U: reqwest::IntoUrl>(&self, url: U) -> Result<reqwest::Response, ApiError> {
-        let mut response = self.get_raw(url)?;
-        if response.status().is_success() {
-            info!("response = {:?}", response);
-            Ok(response)
-        } else {
-            let error_response: ErrorResponse = response.json()?;
-            Err(error_response.error)
-        }
+    fn get(&self, url: &str) -> Result<reqwest::Response, ApiError> {
+        let response = self.get_raw(url)?;
+        info!("response = {:?}", response);
+        check_status(response)
     }
 
     fn get_data<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
         let url = self.props.server_url.clone() + API_BASE_PATH + path;
-        let mut response_raw = self.get_raw(&url)?;
-        if response_raw.status().is_success() {
-            info!("response_raw = {:?}", response_raw);
-            let response: SuccessResponse<T, NoLinks> = response_raw.json()?;
-            Ok(response.data)
-        } else {
-            let error_response: ErrorResponse = response_raw.json()?;
-            Err(error_response.error)
-        }
+        let response_raw = self.get_raw(&url)?;
+        info!("response_raw = {:?}", response_raw);
+        let response: SuccessResponse<T, NoLinks> = check_status(response_raw)?.json()?;
+        Ok(response.data)
     }
 
-    fn post<U: reqwest::IntoUrl, B: ?Sized>(&self, url: U, body: &B) -> reqwest::Result<reqwest::Response>
+    fn post<B: ?Sized>(&self, url: &str, body: &B) -> reqwest::Result<reqwest::Response>
         where B: serde::Serialize
     {
-        let response = reqwest::Client::new()
-            .post(url)
-            .headers(self.create_common_headers())
-            .body(serde_json::to_string(&body).unwrap())
-            .send();
+        let body = serde_json::to_string(&body).unwrap();
+        let response = self.send_with_retry(false, || {
+            self.http_client
+                .post(url)
+                .headers(self.create_common_headers())
+                .body(body.clone())
+        });
 
         info!("response = {:?}", response);
 
         response
     }
 
-    fn delete<U: reqwest::IntoUrl>(&self, url: U) -> reqwest::Result<reqwest::Response> {
-        let response = reqwest::Client::new()
-            .delete(url)
-            .headers(self.create_common_headers())
-            .send();
+    fn delete(&self, url: &str) -> reqwest::Result<reqwest::Response> {
+        let response = self.send_with_retry(true, || {
+            self.http_client
+                .delete(url)
+                .headers(self.create_common_headers())
+        });
 
         info!("response = {:?}", response);
 
         response
     }
 
+    /// Retries transient failures according to the retry policy.
+    /// Requests, which are not `idempotent`, are only retried if they have not been processed by the server.
+    fn send_with_retry<F>(&self, idempotent: bool, create_request: F) -> reqwest::Result<reqwest::Response>
+        where F: Fn() -> reqwest::RequestBuilder
+    {
+        let retry_policy = &self.props.http.retry;
+        let mut attempt = 1;
+        loop {
+            let mut result = create_request().send();
+            if attempt >= retry_policy.max_attempts {
+                return result;
+            }
+
+            let requested_delay = match result {
+                Ok(ref mut response) if is_retryable_status(response.status(), idempotent) => {
+                    info!("Request failed with status {} (attempt {} of {})", response.status(), attempt, retry_policy.max_attempts);
+                    retry_after(response)
+                }
+                Err(ref error) if is_retryable_error(error, idempotent) => {
+                    info!("Request failed with {} (attempt {} of {})", error, attempt, retry_policy.max_attempts);
+                    None
+                }
+                _ => return result,
+            };
+
+            let delay = requested_delay.map(|d| retry_policy.limit(d))
+                .unwrap_or_else(|| retry_policy.backoff(attempt));
+            info!("Retrying in {:?}", delay);
+            thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
     fn create_common_headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
         headers.insert(USER_AGENT, self.props.client.name.parse().unwrap());
@@ -187,4 +241,23 @@ impl AcroApi {
 
         headers
     }
-}
\ No newline at end of file
+}
+
+/// Converts failed responses into errors. If the body is not a problem document of the server,
+/// the problem is derived from the HTTP status.
+fn check_status(mut response: reqwest::Response) -> Result<reqwest::Response, ApiError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let problem = response.text().ok()
+        .and_then(|text| serde_json::from_str::<ErrorResponse>(&text).ok())
+        .map(|error_response| error_response.error)
+        .unwrap_or_else(|| ServerProblem {
+            _type: "httpStatus".to_string(),
+            title: status.canonical_reason().unwrap_or("Request failed").to_string(),
+            detail: format!("The server responded with status {}.", status.as_u16()),
+            status: None,
+        });
+    Err(ApiError::from(ServerProblem { status: problem.status.or_else(|| Some(status.as_u16())), ..problem }))
+}
@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use rand::Rng;
+use reqwest::header::RETRY_AFTER;
+use serde_json::Value;
+
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Includes the first attempt, so 1 disables retries.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    /// Limits the exponential backoff and the delays requested by the server.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter, so that concurrent checks don't retry in lockstep.
+    /// `attempt` is the number of the failed attempt, starting with 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential_millis = (self.initial_backoff.as_millis() as u64)
+            .saturating_mul(1u64 << (attempt - 1).min(16));
+        let capped_millis = exponential_millis.min(self.max_backoff.as_millis() as u64);
+        let jittered_millis = rand::thread_rng().gen_range(capped_millis / 2, capped_millis + 1);
+        Duration::from_millis(jittered_millis)
+    }
+
+    pub fn limit(&self, delay: Duration) -> Duration {
+        delay.min(self.max_backoff)
+    }
+}
+
+/// Requests, which are not idempotent, are only retried if the server signals that it has not processed them.
+pub fn is_retryable_status(status: StatusCode, idempotent: bool) -> bool {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => true,
+        StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT => idempotent,
+        _ => false,
+    }
+}
+
+/// Requests, which are not idempotent, are only retried if the connection could not be established,
+/// because then the request has never reached the server.
+pub fn is_retryable_error(error: &reqwest::Error, idempotent: bool) -> bool {
+    let is_connect_error = error.get_ref()
+        .and_then(|e| e.downcast_ref::<hyper::Error>())
+        .map(hyper::Error::is_connect)
+        .unwrap_or(false);
+    let is_transport_error = error.status().is_none() && !error.is_serialization() && !error.is_redirect();
+    is_connect_error || (idempotent && is_transport_error)
+}
+
+/// Reads the delay from the "Retry-After" header or from the "retryAfter" property of the response body.
+pub fn retry_after(response: &mut reqwest::Response) -> Option<Duration> {
+    let header_delay = response.headers().get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_retry_after_header(value, Utc::now()));
+    if header_delay.is_some() {
+        return header_delay;
+    }
+    response.text().ok().and_then(|text| parse_retry_after_body(&text))
+}
+
+/// The header contains seconds or an HTTP date like "Wed, 21 Oct 2015 07:28:00 GMT".
+fn parse_retry_after_header(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    // Dates in the past allow an immediate retry.
+    Some(date.with_timezone(&Utc).signed_duration_since(now).to_std().unwrap_or_default())
+}
+
+/// The body contains seconds.
+fn parse_retry_after_body(text: &str) -> Option<Duration> {
+    let body: Value = serde_json::from_str(text).ok()?;
+    body["retryAfter"].as_u64()
+        .or_else(|| body["progress"]["retryAfter"].as_u64())
+        .or_else(|| body["error"]["retryAfter"].as_u64())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+
+    fn assert_backoff_between(attempt: u32, min_millis: u64, max_millis: u64) {
+        for _ in 0..100 {
+            let backoff = policy().backoff(attempt);
+            assert!(backoff >= Duration::from_millis(min_millis) && backoff <= Duration::from_millis(max_millis),
+                    "backoff {:?} of attempt {}", backoff, attempt);
+        }
+    }
+
+    #[test]
+    fn doubles_the_backoff_with_jitter() {
+        assert_backoff_between(1, 50, 100);
+        assert_backoff_between(2, 100, 200);
+        assert_backoff_between(3, 200, 400);
+    }
+
+    #[test]
+    fn caps_the_backoff_before_the_jitter() {
+        assert_backoff_between(5, 500, 1000);
+        assert_backoff_between(100, 500, 1000);
+    }
+
+    #[test]
+    fn limits_requested_delays() {
+        assert_eq!(policy().limit(Duration::from_secs(60)), Duration::from_secs(1));
+        assert_eq!(policy().limit(Duration::from_millis(10)), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn retries_overload_for_all_requests() {
+        for &idempotent in &[true, false] {
+            assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS, idempotent));
+            assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE, idempotent));
+        }
+    }
+
+    #[test]
+    fn retries_gateway_errors_only_for_idempotent_requests() {
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY, true));
+        assert!(is_retryable_status(StatusCode::GATEWAY_TIMEOUT, true));
+        assert!(!is_retryable_status(StatusCode::BAD_GATEWAY, false));
+        assert!(!is_retryable_status(StatusCode::GATEWAY_TIMEOUT, false));
+    }
+
+    #[test]
+    fn does_not_retry_other_statuses() {
+        for &status in &[StatusCode::OK, StatusCode::BAD_REQUEST, StatusCode::UNAUTHORIZED, StatusCode::INTERNAL_SERVER_ERROR] {
+            assert!(!is_retryable_status(status, true));
+        }
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        let now = Utc.ymd(2015, 10, 21).and_hms(7, 27, 30);
+        assert_eq!(parse_retry_after_header(" 120 ", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after_header("Wed, 21 Oct 2015 07:28:00 GMT", now), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after_header("Wed, 21 Oct 2015 07:00:00 GMT", now), Some(Duration::from_secs(0)));
+        assert_eq!(parse_retry_after_header("soon", now), None);
+    }
+
+    #[test]
+    fn parses_retry_after_of_the_body() {
+        assert_eq!(parse_retry_after_body(r#"{"retryAfter": 3}"#), Some(Duration::from_secs(3)));
+        assert_eq!(parse_retry_after_body(r#"{"progress": {"retryAfter": 2}}"#), Some(Duration::from_secs(2)));
+        assert_eq!(parse_retry_after_body(r#"{"error": {"retryAfter": 1}}"#), Some(Duration::from_secs(1)));
+        assert_eq!(parse_retry_after_body("Service Unavailable"), None);
+    }
+}
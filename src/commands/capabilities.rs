@@ -5,16 +5,19 @@ use crate::commands::common::connect_and_signin;
 use crate::commands::common::CommonCommandConfig;
 use crate::api::checking::GuidanceProfile;
 use std::fmt::Debug;
+use crate::api::errors::ApiError;
+use crate::commands::errors::CommandError;
 
-pub fn show_capabilities(config: &CommonCommandConfig) {
-    let api = connect_and_signin(&config).api;
+pub fn show_capabilities(config: &CommonCommandConfig) -> Result<(), CommandError> {
+    let api = connect_and_signin(&config)?.api;
     info!("{:?}", api.server_info());
-    let capabilities = api.get_checking_capabilities().unwrap();
+    let capabilities = api.get_checking_capabilities()?;
     if config.silent {
-        println!("{}", serde_json::to_string_pretty(&capabilities).unwrap());
+        println!("{}", serde_json::to_string_pretty(&capabilities).map_err(ApiError::from)?);
     } else {
         print_capabilities_for_humans(&capabilities);
     }
+    Ok(())
 }
 
 fn print_capabilities_for_humans(capabilities: &CheckingCapabilities) {
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use crate::api::checking::{ContentFormat, ContentFormatId};
+
+/// Content formats for file extensions. The first format, which the server supports, is used.
+static CONTENT_FORMATS_BY_EXTENSION: &[(&[&str], &[&str])] = &[
+    (&["md", "markdown"], &["MARKDOWN"]),
+    (&["dita", "ditamap"], &["DITA", "XML"]),
+    (&["xml", "xliff", "xlf", "svg"], &["XML"]),
+    (&["html", "htm", "xhtml"], &["HTML", "XML"]),
+    (&["txt", "text"], &["TEXT"]),
+    (&["json"], &["JSON"]),
+    (&["yaml", "yml"], &["YAML"]),
+    (&["properties"], &["PROPERTIES"]),
+];
+
+/// Documents, which are sent as bytes, because they are no plain text files.
+static BINARY_EXTENSIONS: &[&str] = &["doc", "docx", "docm", "dotx", "odt", "pdf", "ppt", "pptx", "xls", "xlsx", "idml"];
+
+pub fn is_binary_file(path: &str) -> bool {
+    Path::new(path).extension()
+        .map(|extension| BINARY_EXTENSIONS.contains(&extension.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Finds the content format by id or display name (case insensitive).
+pub fn resolve_content_format(content_formats: &[ContentFormat], selector: &str) -> Result<ContentFormatId, String> {
+    content_formats.iter()
+        .find(|format| format.id.eq_ignore_ascii_case(selector) || format.displayName.eq_ignore_ascii_case(selector))
+        .map(|format| format.id.clone())
+        .ok_or_else(|| {
+            let ids: Vec<&str> = content_formats.iter().map(|format| format.id.as_str()).collect();
+            format!("Unknown content format \"{}\". Available content formats: {}", selector, ids.join(", "))
+        })
+}
+
+/// Returns `None` for unknown extensions, so that the server detects the format.
+pub fn detect_content_format(content_formats: &[ContentFormat], path: &str) -> Option<ContentFormatId> {
+    let extension = Path::new(path).extension()?.to_string_lossy().to_lowercase();
+    let (_, candidates) = CONTENT_FORMATS_BY_EXTENSION.iter()
+        .find(|(extensions, _)| extensions.contains(&extension.as_str()))?;
+    candidates.iter()
+        .filter_map(|candidate| content_formats.iter().find(|format| format.id.eq_ignore_ascii_case(candidate)))
+        .map(|format| format.id.clone())
+        .next()
+}
@@ -0,0 +1,148 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// The encoding of a text file, which is decoded to UTF-8 for the check
+/// and encoded again for writing fixes or reporting offsets of the original bytes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextEncoding {
+    pub encoding: &'static Encoding,
+    /// Whether the file starts with a byte order mark.
+    pub bom: bool,
+}
+
+impl Default for TextEncoding {
+    fn default() -> Self {
+        TextEncoding { encoding: UTF_8, bom: false }
+    }
+}
+
+impl TextEncoding {
+    /// A byte order mark wins over the `fallback` encoding, which defaults to UTF-8.
+    pub fn decode(bytes: &[u8], fallback: Option<&'static Encoding>) -> Result<(String, TextEncoding), String> {
+        let (text_encoding, text_bytes) = match Encoding::for_bom(bytes) {
+            Some((encoding, bom_length)) => (TextEncoding { encoding, bom: true }, &bytes[bom_length..]),
+            None => (TextEncoding { encoding: fallback.unwrap_or(UTF_8), bom: false }, bytes),
+        };
+
+        let (text, had_errors) = text_encoding.encoding.decode_without_bom_handling(text_bytes);
+        if had_errors {
+            return Err(if text_encoding.bom {
+                format!("The file is no valid {}, although it starts with its byte order mark.", text_encoding.name())
+            } else {
+                format!("The file is no valid {}. Please set the right --encoding.", text_encoding.name())
+            });
+        }
+        Ok((text.into_owned(), text_encoding))
+    }
+
+    /// Fails for characters, which the encoding can't represent, like "→" in windows-1252.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, String> {
+        let mut bytes = vec![];
+        if self.bom {
+            bytes.extend_from_slice(self.bom_bytes());
+        }
+        bytes.extend(self.encode_without_bom(text)?);
+        Ok(bytes)
+    }
+
+    /// Maps a byte offset in the decoded text to the byte offset in the original file.
+    pub fn original_byte_offset(&self, text: &str, byte_offset: usize) -> usize {
+        let bom_length = if self.bom { self.bom_bytes().len() } else { 0 };
+        if self.encoding == UTF_8 {
+            bom_length + byte_offset
+        } else {
+            // The text has been decoded from the file, so it can always be encoded again.
+            bom_length + self.encode_without_bom(&text[..byte_offset]).map_or(byte_offset, |bytes| bytes.len())
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    fn bom_bytes(&self) -> &'static [u8] {
+        if self.encoding == UTF_16LE {
+            &[0xFF, 0xFE]
+        } else if self.encoding == UTF_16BE {
+            &[0xFE, 0xFF]
+        } else {
+            &[0xEF, 0xBB, 0xBF]
+        }
+    }
+
+    /// encoding_rs can't encode UTF-16, because browsers never need it.
+    fn encode_without_bom(&self, text: &str) -> Result<Vec<u8>, String> {
+        if self.encoding == UTF_16LE {
+            Ok(text.encode_utf16().flat_map(|unit| unit.to_le_bytes().to_vec()).collect())
+        } else if self.encoding == UTF_16BE {
+            Ok(text.encode_utf16().flat_map(|unit| unit.to_be_bytes().to_vec()).collect())
+        } else {
+            // encoding_rs replaces unmappable characters by HTML entities like "&#8594;".
+            let (bytes, _, has_unmappable_characters) = self.encoding.encode(text);
+            if has_unmappable_characters {
+                let character = text.chars()
+                    .find(|c| self.encoding.encode(c.encode_utf8(&mut [0; 4])).2)
+                    .unwrap_or_default();
+                return Err(format!("{} can't encode the character \"{}\"", self.name(), character));
+            }
+            Ok(bytes.into_owned())
+        }
+    }
+}
+
+/// Finds the encoding by a label like "latin1", "windows-1252" or "utf-16le".
+pub fn resolve_encoding(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| format!("Unknown encoding \"{}\"", label))
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::WINDOWS_1252;
+
+    use super::*;
+
+    fn round_trip(bytes: &[u8], fallback: Option<&'static Encoding>) -> (String, TextEncoding) {
+        let (text, text_encoding) = TextEncoding::decode(bytes, fallback).unwrap();
+        assert_eq!(text_encoding.encode(&text).unwrap(), bytes);
+        (text, text_encoding)
+    }
+
+    #[test]
+    fn keeps_the_byte_order_mark() {
+        let (text, text_encoding) = round_trip(b"\xEF\xBB\xBFh\xC3\xA4", None);
+        assert_eq!(text, "hä");
+        assert_eq!(text_encoding, TextEncoding { encoding: UTF_8, bom: true });
+    }
+
+    #[test]
+    fn encodes_utf16() {
+        let (text, text_encoding) = round_trip(b"\xFF\xFEh\x00\xE4\x00", None);
+        assert_eq!(text, "hä");
+        assert_eq!(text_encoding, TextEncoding { encoding: UTF_16LE, bom: true });
+
+        let (text, text_encoding) = round_trip(b"\xFE\xFF\xD8\x3D\xDE\x00", None);
+        assert_eq!(text, "😀");
+        assert_eq!(text_encoding, TextEncoding { encoding: UTF_16BE, bom: true });
+    }
+
+    #[test]
+    fn encodes_legacy_encodings() {
+        let (text, text_encoding) = round_trip(b"h\xE4", Some(WINDOWS_1252));
+        assert_eq!(text, "hä");
+        assert_eq!(text_encoding.original_byte_offset(&text, "hä".len()), 2);
+    }
+
+    #[test]
+    fn names_the_encoding_of_invalid_files() {
+        assert_eq!(TextEncoding::decode(b"h\xE4", None).unwrap_err(),
+                   "The file is no valid UTF-8. Please set the right --encoding.");
+        assert_eq!(TextEncoding::decode(b"\xEF\xBB\xBFh\xE4", Some(WINDOWS_1252)).unwrap_err(),
+                   "The file is no valid UTF-8, although it starts with its byte order mark.");
+    }
+
+    #[test]
+    fn rejects_unmappable_characters() {
+        let text_encoding = TextEncoding { encoding: WINDOWS_1252, bom: false };
+        assert_eq!(text_encoding.encode("a → b"), Err("windows-1252 can't encode the character \"→\"".to_string()));
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use glob::{glob, Pattern};
+use ignore::WalkBuilder;
+
+use crate::commands::errors::CommandError;
+
+/// Files in directories are only checked if they have one of these extensions, unless `--include` is given.
+pub static DEFAULT_EXTENSIONS: &[&str] = &[
+    "txt", "text", "md", "markdown", "xml", "html", "htm", "xhtml", "dita", "ditamap", "xliff", "xlf"
+];
+
+/// Like .gitignore files, but only for acrusto.
+pub static IGNORE_FILE_NAME: &str = ".acrustoignore";
+
+/// Selects the files in directories, which should be checked.
+pub struct FileFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    /// Skips files in directories, which are ignored by .gitignore or .acrustoignore files.
+    use_ignore_files: bool,
+}
+
+impl FileFilter {
+    pub fn new(include: &[String], exclude: &[String], use_ignore_files: bool) -> Result<Self, CommandError> {
+        Ok(FileFilter {
+            include: compile_patterns(include, "include")?,
+            exclude: compile_patterns(exclude, "exclude")?,
+            use_ignore_files,
+        })
+    }
+
+    pub fn is_excluded(&self, path: &str) -> bool {
+        self.exclude.iter().any(|pattern| matches_path(pattern, path))
+    }
+
+    /// Files of directories must match an include pattern or have a default extension.
+    fn is_included(&self, path: &str) -> bool {
+        if self.include.is_empty() {
+            Path::new(path).extension()
+                .map(|extension| DEFAULT_EXTENSIONS.contains(&extension.to_string_lossy().to_lowercase().as_str()))
+                .unwrap_or(false)
+        } else {
+            self.include.iter().any(|pattern| matches_path(pattern, path))
+        }
+    }
+}
+
+/// Expands the file patterns and searches directories recursively.
+/// Explicitly named files are always checked unless they are excluded.
+/// The result is sorted and contains no duplicates.
+pub fn find_files(file_patterns: &[String], filter: FileFilter) -> Result<Vec<String>, CommandError> {
+    let filter = Arc::new(filter);
+    let mut files = BTreeSet::new();
+
+    for file_pattern in file_patterns {
+        let found_paths = glob(file_pattern)
+            .map_err(|error| CommandError::Usage(format!("Invalid file pattern \"{}\": {}", file_pattern, error)))?
+            .filter_map(Result::ok);
+
+        for path in found_paths {
+            if path.is_dir() {
+                collect_directory_files(&path, &filter, &mut files)
+                    .map_err(|error| CommandError::Io(format!("Can't read the directory {} because of {}", path.display(), error)))?;
+            } else {
+                let path = path.to_string_lossy().to_string();
+                if !filter.is_excluded(&path) {
+                    files.insert(path);
+                }
+            }
+        }
+    }
+
+    Ok(files.into_iter().collect())
+}
+
+/// Hidden files and directories like ".git" are skipped. Symbolic links to directories are not followed.
+/// Ignore files apply like in git, even outside of git repositories.
+fn collect_directory_files(dir: &Path, filter: &Arc<FileFilter>, files: &mut BTreeSet<String>) -> Result<(), ignore::Error> {
+    let filter_for_walker = filter.clone();
+    let mut builder = WalkBuilder::new(dir);
+    builder
+        .hidden(true)
+        .follow_links(false)
+        .ignore(false)
+        .git_ignore(filter.use_ignore_files)
+        .git_global(filter.use_ignore_files)
+        .git_exclude(filter.use_ignore_files)
+        .parents(filter.use_ignore_files)
+        .require_git(false)
+        .filter_entry(move |entry| !filter_for_walker.is_excluded(&entry.path().to_string_lossy()));
+    if filter.use_ignore_files {
+        builder.add_custom_ignore_filename(IGNORE_FILE_NAME);
+    }
+    let walker = builder.build();
+
+    for entry in walker {
+        let entry = entry?;
+        let path_string = entry.path().to_string_lossy().to_string();
+        if entry.file_type().is_some_and(|file_type| file_type.is_file()) && filter.is_included(&path_string) {
+            files.insert(path_string);
+        }
+    }
+    Ok(())
+}
+
+fn compile_patterns(patterns: &[String], kind: &str) -> Result<Vec<Pattern>, CommandError> {
+    patterns.iter()
+        .map(|pattern| Pattern::new(normalize_path(pattern))
+            .map_err(|error| CommandError::Usage(format!("Invalid {} pattern \"{}\": {}", kind, pattern, error))))
+        .collect()
+}
+
+pub fn normalize_path(path: &str) -> &str {
+    path.trim_start_matches("./")
+}
+
+/// Absolute patterns of project configs are matched against the absolute path,
+/// other patterns against the path relative to the current directory.
+pub fn matches_path(pattern: &Pattern, path: &str) -> bool {
+    if Path::new(pattern.as_str()).is_absolute() {
+        absolute_path(path).is_some_and(|absolute_path| pattern.matches(&absolute_path.to_string_lossy()))
+    } else {
+        pattern.matches(normalize_path(path))
+    }
+}
+
+/// Resolves "." and ".." without following symbolic links, like the patterns do.
+pub fn absolute_path(path: &str) -> Option<PathBuf> {
+    let mut absolute_path = env::current_dir().ok()?;
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute_path.pop();
+            }
+            component => absolute_path.push(component),
+        }
+    }
+    Some(absolute_path)
+}
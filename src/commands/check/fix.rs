@@ -0,0 +1,428 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+
+use crate::api::AcroApi;
+use crate::api::checking::{Issue, OffsetRange, Suggestion};
+use crate::commands::check::{CheckedDocument, DocumentContent, FileCheckResult};
+use crate::commands::check::{check_content, with_partial_check_ranges};
+use crate::commands::check::progress::silent_progress::SilentProgressReporter;
+use crate::commands::check::source_text::SourceText;
+use crate::commands::check::encoding::TextEncoding;
+
+const PATCH_CONTEXT_LINES: usize = 3;
+
+pub enum FixMode {
+    /// Rewrites the checked files.
+    InPlace,
+    /// Writes the fixes as unified diff into the given file and keeps the checked files untouched.
+    Patch(PathBuf),
+}
+
+/// Replaces the bytes [begin, end) of the checked content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replacement {
+    pub begin: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl Replacement {
+    pub fn overlaps(&self, other: &Replacement) -> bool {
+        (self.begin < other.end && other.begin < self.end) || self.begin == other.begin
+    }
+}
+
+/// Applies the first suggestion of every issue and rechecks the fixed content.
+/// In place fixes replace the results of the fixed files.
+pub fn fix_files(api: &AcroApi, results: &mut [FileCheckResult], fix_mode: &FixMode, stop_requested: &AtomicBool, verbose: bool) -> io::Result<()> {
+    let mut patch = String::new();
+
+    for file_result in results.iter_mut() {
+        let replacements = match file_result.result {
+            Ok(ref checked_document) => collect_replacements(checked_document),
+            Err(_) => continue,
+        };
+        if replacements.is_empty() {
+            continue;
+        }
+        if let Some(file_patch) = fix_file(api, file_result, &replacements, fix_mode, stop_requested, verbose)? {
+            patch.push_str(&file_patch);
+        }
+    }
+
+    if let FixMode::Patch(ref patch_path) = fix_mode {
+        fs::write(patch_path, patch)?;
+    }
+
+    Ok(())
+}
+
+/// Applies the replacements to a successfully checked file and rechecks it.
+/// Returns the patch of the file in patch mode.
+pub fn fix_file(api: &AcroApi, file_result: &mut FileCheckResult,
+                replacements: &[Replacement], fix_mode: &FixMode, stop_requested: &AtomicBool,
+                verbose: bool) -> io::Result<Option<String>> {
+    let (fixed_content, text_encoding, patch, recheck_options) = match file_result.result {
+        Ok(CheckedDocument { content: DocumentContent::Text(ref content, text_encoding), ref partial_check_ranges, .. }) => {
+            let patch = match fix_mode {
+                FixMode::Patch(_) => Some(create_patch(&file_result.path, content, replacements)),
+                FixMode::InPlace => None,
+            };
+            let fixed_content = apply_replacements(content, replacements);
+            // A partial check is repeated for the same text, so that the scores are comparable.
+            let recheck_options = match partial_check_ranges {
+                Some(ranges) => with_partial_check_ranges(&file_result.check_options,
+                                                          remap_ranges(content, &fixed_content, ranges, replacements)),
+                None => (*file_result.check_options).clone(),
+            };
+            (fixed_content, text_encoding, patch, recheck_options)
+        }
+        _ => return Ok(None),
+    };
+
+    if let FixMode::InPlace = fix_mode {
+        let fixed_bytes = match text_encoding.encode(&fixed_content) {
+            Ok(fixed_bytes) => fixed_bytes,
+            Err(error) => {
+                eprintln!("Skipped fixing {} because {}.", file_result.path, error);
+                return Ok(None);
+            }
+        };
+        if !write_fixed_file(file_result, &fixed_bytes, text_encoding)? {
+            eprintln!("Skipped fixing {} because it has been changed since the check.", file_result.path);
+            return Ok(None);
+        }
+    }
+
+    let reference = file_result.reference.clone().unwrap_or_else(|| file_result.path.clone());
+    let recheck_result = check_content(api, &recheck_options, DocumentContent::Text(fixed_content, text_encoding), &reference,
+                                       &SilentProgressReporter {}, stop_requested);
+
+    if verbose {
+        let old_score = file_result.quality().map(|q| q.score).ok();
+        match recheck_result {
+            Ok(ref checked_document) => println!("Fixed {} replacement(s) in {}: score {} -> {}",
+                                                 replacements.len(), file_result.path,
+                                                 old_score.unwrap_or_default(), checked_document.check_result.quality.score),
+            Err(ref error) => println!("Fixed {} replacement(s) in {}, but the recheck failed: {}",
+                                       replacements.len(), file_result.path, error.title()),
+        }
+    }
+
+    if let FixMode::InPlace = fix_mode {
+        file_result.result = recheck_result;
+    }
+
+    Ok(patch)
+}
+
+/// Returns false if the file on disk differs from the checked content.
+/// The fixed content must be encoded in the original encoding of the file.
+fn write_fixed_file(file_result: &FileCheckResult, fixed_bytes: &[u8], text_encoding: TextEncoding) -> io::Result<bool> {
+    let checked_content = match file_result.result.as_ref().ok().and_then(|d| d.content.text()) {
+        Some(content) => content,
+        None => return Ok(false),
+    };
+    if Ok(fs::read(&file_result.path)?) != text_encoding.encode(checked_content) {
+        return Ok(false);
+    }
+    fs::write(&file_result.path, fixed_bytes)?;
+    Ok(true)
+}
+
+/// Returns the replacements of a suggestion or `None` if they don't fit to the matches of the issue.
+/// Matches, which are reversed or overlap each other, can't be replaced safely.
+pub fn suggestion_replacements(issue: &Issue, suggestion: &Suggestion, source_text: &SourceText) -> Option<Vec<Replacement>> {
+    let matches = &issue.positionalInformation.matches;
+    if matches.is_empty() || suggestion.replacements.len() != matches.len() ||
+        matches.iter().any(|issue_match| issue_match.originalEnd < issue_match.originalBegin) {
+        return None;
+    }
+
+    let mut replacements: Vec<Replacement> = matches.iter().zip(&suggestion.replacements)
+        .filter_map(|(issue_match, replacement)| {
+            replacement.as_ref().map(|text| Replacement {
+                begin: source_text.byte_offset(issue_match.originalBegin),
+                end: source_text.byte_offset(issue_match.originalEnd),
+                text: text.clone(),
+            })
+        })
+        .collect();
+
+    replacements.sort_by_key(|r| r.begin);
+    if replacements.windows(2).any(|pair| pair[0].overlaps(&pair[1])) {
+        return None;
+    }
+    Some(replacements)
+}
+
+/// Uses the first suggestion of every issue. Issues, which overlap the replacements of
+/// a previous issue, are skipped. The result is sorted by position.
+/// Binary documents can't be fixed.
+pub fn collect_replacements(checked_document: &CheckedDocument) -> Vec<Replacement> {
+    let source_text = match checked_document.content.text() {
+        Some(content) => SourceText::new(content),
+        None => return vec![],
+    };
+    let mut accepted: Vec<Replacement> = vec![];
+
+    for issue in &checked_document.check_result.issues {
+        if issue.readOnly {
+            continue;
+        }
+        let replacements = issue.suggestions.first()
+            .and_then(|suggestion| suggestion_replacements(issue, suggestion, &source_text));
+        if let Some(replacements) = replacements {
+            if !replacements.iter().any(|r| accepted.iter().any(|a| a.overlaps(r))) {
+                accepted.extend(replacements);
+            }
+        }
+    }
+
+    accepted.sort_by_key(|r| r.begin);
+    accepted
+}
+
+/// The replacements must not overlap. They are applied from the end of the content backwards,
+/// so that the offsets of the remaining replacements stay valid.
+pub fn apply_replacements(content: &str, replacements: &[Replacement]) -> String {
+    let mut sorted_replacements: Vec<&Replacement> = replacements.iter().collect();
+    sorted_replacements.sort_by_key(|r| r.begin);
+
+    let mut result = content.to_string();
+    for replacement in sorted_replacements.into_iter().rev() {
+        result.replace_range(replacement.begin..replacement.end, &replacement.text);
+    }
+    result
+}
+
+/// Moves the ranges of the content to the fixed content. Replacements at the edges of a range belong to it.
+fn remap_ranges(content: &str, fixed_content: &str, ranges: &[OffsetRange], replacements: &[Replacement]) -> Vec<OffsetRange> {
+    let mut sorted_replacements: Vec<&Replacement> = replacements.iter().collect();
+    sorted_replacements.sort_by_key(|r| r.begin);
+    let source_text = SourceText::new(content);
+    let api_offset = |byte_offset: usize| fixed_content[..byte_offset].encode_utf16().count();
+    ranges.iter()
+        .map(|range| OffsetRange {
+            begin: api_offset(remap_byte_offset(source_text.byte_offset(range.begin), &sorted_replacements, false)),
+            end: api_offset(remap_byte_offset(source_text.byte_offset(range.end), &sorted_replacements, true)),
+        })
+        .collect()
+}
+
+/// Offsets within a replacement move to its start or, for the `end` of a range, to its end.
+/// The replacements must be sorted.
+fn remap_byte_offset(byte_offset: usize, sorted_replacements: &[&Replacement], end: bool) -> usize {
+    let mut fixed_offset = byte_offset;
+    for replacement in sorted_replacements {
+        let insertion_at_end = end && replacement.begin == byte_offset && replacement.end == byte_offset;
+        if replacement.begin >= byte_offset && !insertion_at_end {
+            break;
+        }
+        let fixed_begin = fixed_offset + replacement.begin - byte_offset;
+        if replacement.end > byte_offset {
+            return if end { fixed_begin + replacement.text.len() } else { fixed_begin };
+        }
+        fixed_offset = fixed_offset + replacement.text.len() - (replacement.end - replacement.begin);
+    }
+    fixed_offset
+}
+
+/// Creates a unified diff of the replacements with some lines of context.
+pub fn create_patch(path: &str, content: &str, replacements: &[Replacement]) -> String {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .filter(|&start| start == 0 || start < content.len())
+        .collect();
+    let line_of = |byte_offset: usize| match line_starts.binary_search(&byte_offset) {
+        Ok(index) => index,
+        Err(index) => index.max(1) - 1,
+    };
+    let line_end = |line: usize| line_starts.get(line + 1).cloned().unwrap_or(content.len());
+
+    // Group the replacements into hunks of changed lines [first, last].
+    let mut hunks: Vec<(usize, usize, Vec<&Replacement>)> = vec![];
+    for replacement in replacements {
+        let first = line_of(replacement.begin);
+        let last = line_of(replacement.end.max(replacement.begin + 1) - 1);
+        match hunks.last_mut() {
+            Some(hunk) if first <= hunk.1 + 2 * PATCH_CONTEXT_LINES + 1 => {
+                hunk.1 = hunk.1.max(last);
+                hunk.2.push(replacement);
+            }
+            _ => hunks.push((first, last, vec![replacement])),
+        }
+    }
+
+    let normalized_path = path.trim_start_matches("./").replace('\\', "/");
+    let mut patch = format!("--- a/{}\n+++ b/{}\n", normalized_path, normalized_path);
+    let mut line_delta: isize = 0;
+
+    for (first, last, hunk_replacements) in hunks {
+        let old_first = first.saturating_sub(PATCH_CONTEXT_LINES);
+        let old_last = (last + PATCH_CONTEXT_LINES).min(line_starts.len() - 1);
+        let hunk_begin = line_starts[old_first];
+        let hunk_end = line_end(old_last);
+
+        let old_text = &content[hunk_begin..hunk_end];
+        let shifted_replacements: Vec<Replacement> = hunk_replacements.iter().map(|r| Replacement {
+            begin: r.begin - hunk_begin,
+            end: r.end - hunk_begin,
+            text: r.text.clone(),
+        }).collect();
+        let new_text = apply_replacements(old_text, &shifted_replacements);
+
+        let old_lines: Vec<&str> = old_text.split_inclusive('\n').collect();
+        let new_lines: Vec<&str> = new_text.split_inclusive('\n').collect();
+        let new_first = (old_first as isize + line_delta) as usize;
+        line_delta += new_lines.len() as isize - old_lines.len() as isize;
+
+        patch.push_str(&format!("@@ -{},{} +{},{} @@\n", old_first + 1, old_lines.len(), new_first + 1, new_lines.len()));
+
+        let common_prefix = old_lines.iter().zip(&new_lines).take_while(|(o, n)| o == n).count();
+        let max_suffix = old_lines.len().min(new_lines.len()) - common_prefix;
+        let common_suffix = old_lines.iter().rev().zip(new_lines.iter().rev())
+            .take(max_suffix)
+            .take_while(|(o, n)| o == n)
+            .count();
+
+        for line in &old_lines[..common_prefix] {
+            push_patch_line(&mut patch, ' ', line);
+        }
+        for line in &old_lines[common_prefix..old_lines.len() - common_suffix] {
+            push_patch_line(&mut patch, '-', line);
+        }
+        for line in &new_lines[common_prefix..new_lines.len() - common_suffix] {
+            push_patch_line(&mut patch, '+', line);
+        }
+        for line in &old_lines[old_lines.len() - common_suffix..] {
+            push_patch_line(&mut patch, ' ', line);
+        }
+    }
+
+    patch
+}
+
+fn push_patch_line(patch: &mut String, prefix: char, line: &str) {
+    patch.push(prefix);
+    patch.push_str(line);
+    if !line.ends_with('\n') {
+        patch.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::api::checking::CheckResult;
+    use crate::commands::check::encoding::TextEncoding;
+    use super::*;
+
+    fn replacement(begin: usize, end: usize, text: &str) -> Replacement {
+        Replacement { begin, end, text: text.to_string() }
+    }
+
+    /// Each match is (originalBegin, originalEnd) and gets the replacement of the first suggestion.
+    fn issue(matches: &[(usize, usize)], replacements: &[&str]) -> Issue {
+        serde_json::from_value(json!({
+            "goalId": "spelling",
+            "displaySurface": "",
+            "positionalInformation": {
+                "matches": matches.iter()
+                    .map(|(begin, end)| json!({"originalPart": "", "originalBegin": begin, "originalEnd": end}))
+                    .collect::<Vec<_>>(),
+            },
+            "suggestions": [{"surface": "", "replacements": replacements}],
+        })).unwrap()
+    }
+
+    fn checked_document(content: &str, issues: Vec<Issue>) -> CheckedDocument {
+        let mut check_result: CheckResult = serde_json::from_value(json!({
+            "quality": {"score": 50.0, "status": "red"},
+        })).unwrap();
+        check_result.issues = issues;
+        CheckedDocument {
+            content: DocumentContent::Text(content.to_string(), TextEncoding::default()),
+            check_result,
+            partial_check_ranges: None,
+        }
+    }
+
+    #[test]
+    fn applies_unsorted_replacements() {
+        let replacements = [replacement(8, 13, "earth"), replacement(0, 5, "Hi")];
+        assert_eq!(apply_replacements("Hello, world!", &replacements), "Hi, wearth");
+    }
+
+    #[test]
+    fn applies_insertions_and_deletions() {
+        let replacements = [replacement(0, 0, ">"), replacement(3, 4, "")];
+        assert_eq!(apply_replacements("abcd", &replacements), ">abc");
+    }
+
+    #[test]
+    fn collects_replacements_in_utf8_offsets() {
+        // The API offsets count UTF-16 code units, so "😀" has the length 2.
+        let document = checked_document("😀 teh end", vec![issue(&[(3, 6)], &["the"])]);
+        let replacements = collect_replacements(&document);
+        assert_eq!(replacements, vec![replacement(5, 8, "the")]);
+        assert_eq!(apply_replacements("😀 teh end", &replacements), "😀 the end");
+    }
+
+    #[test]
+    fn skips_issues_which_overlap_previous_ones() {
+        let document = checked_document("aaa bbb ccc", vec![
+            issue(&[(0, 7)], &["x"]),
+            issue(&[(4, 7)], &["y"]),
+            issue(&[(8, 11)], &["z"]),
+        ]);
+        assert_eq!(collect_replacements(&document), vec![replacement(0, 7, "x"), replacement(8, 11, "z")]);
+    }
+
+    #[test]
+    fn skips_read_only_issues() {
+        let mut read_only_issue = issue(&[(0, 3)], &["x"]);
+        read_only_issue.readOnly = true;
+        assert!(collect_replacements(&checked_document("aaa", vec![read_only_issue])).is_empty());
+    }
+
+    #[test]
+    fn drops_suggestions_with_reversed_matches() {
+        let document = checked_document("aaa bbb", vec![issue(&[(3, 1)], &["x"])]);
+        assert!(collect_replacements(&document).is_empty());
+    }
+
+    #[test]
+    fn drops_suggestions_with_overlapping_matches() {
+        let document = checked_document("aaa bbb", vec![issue(&[(4, 7), (0, 5)], &["x", "y"])]);
+        assert!(collect_replacements(&document).is_empty());
+    }
+
+    #[test]
+    fn sorts_the_matches_of_a_suggestion() {
+        let document = checked_document("aaa bbb", vec![issue(&[(4, 7), (0, 3)], &["x", "y"])]);
+        assert_eq!(collect_replacements(&document), vec![replacement(0, 3, "y"), replacement(4, 7, "x")]);
+    }
+
+    #[test]
+    fn remaps_ranges_behind_replacements() {
+        let content = "aa bb cc";
+        let replacements = [replacement(0, 2, "xxxx")];
+        let fixed_content = apply_replacements(content, &replacements);
+        let ranges = remap_ranges(content, &fixed_content, &[OffsetRange { begin: 3, end: 5 }], &replacements);
+        assert_eq!(ranges, vec![OffsetRange { begin: 5, end: 7 }]);
+    }
+
+    #[test]
+    fn remapped_ranges_contain_replacements_at_their_edges() {
+        let content = "aa bb cc";
+        let replacements = [replacement(2, 4, "_"), replacement(4, 5, "yyy"), replacement(6, 6, "!")];
+        let fixed_content = apply_replacements(content, &replacements);
+        assert_eq!(fixed_content, "aa_yyy !cc");
+        let ranges = remap_ranges(content, &fixed_content, &[OffsetRange { begin: 3, end: 6 }], &replacements);
+        assert_eq!(ranges, vec![OffsetRange { begin: 2, end: 8 }]);
+    }
+}
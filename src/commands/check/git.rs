@@ -0,0 +1,149 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::commands::errors::CommandError;
+
+/// Selects the files, which git reports as changed or added.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GitChanges {
+    /// Changes between the commit and the working tree.
+    Since(String),
+    /// Changes in the index, which would be committed.
+    Staged,
+}
+
+impl GitChanges {
+    /// Replaces the commit by its id, because git would read a value like "--output=file" as option.
+    pub fn resolve(&self) -> Result<GitChanges, CommandError> {
+        match self {
+            GitChanges::Since(commit) => {
+                if commit.starts_with('-') {
+                    return Err(CommandError::Usage(format!("Invalid commit \"{}\"", commit)));
+                }
+                let commit_object = format!("{}^{{commit}}", commit);
+                let commit_id = run_git(&["rev-parse", "--verify", "--quiet", "--end-of-options", &commit_object])
+                    .map_err(|_| CommandError::Usage(format!("Unknown commit \"{}\"", commit)))?;
+                Ok(GitChanges::Since(commit_id.trim_end().to_string()))
+            }
+            GitChanges::Staged => Ok(GitChanges::Staged),
+        }
+    }
+
+    fn diff_args<'a>(&'a self, options: &[&'a str]) -> Vec<&'a str> {
+        let mut args = vec!["diff"];
+        args.extend_from_slice(options);
+        match self {
+            GitChanges::Since(commit) => args.push(commit),
+            GitChanges::Staged => args.push("--cached"),
+        }
+        // Paths follow.
+        args.push("--");
+        args
+    }
+}
+
+/// Returns the canonical paths of the changed files, which still exist.
+/// Changes since a commit include new files, which are not ignored but not added yet.
+pub fn changed_files(changes: &GitChanges) -> Result<BTreeSet<PathBuf>, CommandError> {
+    let toplevel = toplevel()?;
+    let mut names = run_git(&changes.diff_args(&["--name-only", "-z", "--diff-filter=ACMR"]))?;
+    if let GitChanges::Since(_) = changes {
+        // ":/" lists the files of the whole repository, not only of the current directory.
+        names.push_str(&run_git(&["ls-files", "--others", "--exclude-standard", "--full-name", "-z", "--", ":/"])?);
+    }
+    Ok(names.split('\0')
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| fs::canonicalize(toplevel.join(name)).ok())
+        .collect())
+}
+
+/// Keeps the found files, which are changed.
+pub fn filter_changed_files(files: Vec<String>, changed_files: &BTreeSet<PathBuf>) -> Vec<String> {
+    files.into_iter()
+        .filter(|file| fs::canonicalize(file).is_ok_and(|path| changed_files.contains(&path)))
+        .collect()
+}
+
+/// Returns the 1-based lines of the file, which have been changed or added.
+/// Deleted lines are missing, because there is nothing to check.
+pub fn changed_lines(changes: &GitChanges, path: &str) -> Result<Vec<Range<usize>>, CommandError> {
+    let mut args = changes.diff_args(&["--unified=0", "--no-color", "--no-ext-diff"]);
+    args.push(path);
+    let diff = run_git(&args)?;
+    Ok(diff.lines()
+        .filter(|line| line.starts_with("@@ "))
+        .filter_map(parse_hunk_header)
+        .filter(|lines| !lines.is_empty())
+        .collect())
+}
+
+/// Parses the new lines of a hunk header like "@@ -12,3 +12,4 @@ Heading".
+fn parse_hunk_header(line: &str) -> Option<Range<usize>> {
+    let new_lines = line.split(' ').find(|part| part.starts_with('+'))?;
+    let mut numbers = new_lines[1..].splitn(2, ',');
+    let start: usize = numbers.next()?.parse().ok()?;
+    let count: usize = match numbers.next() {
+        Some(count) => count.parse().ok()?,
+        None => 1,
+    };
+    Some(start..start + count)
+}
+
+/// Reads the content of the file, which would be committed.
+pub fn staged_content(path: &str) -> Result<Vec<u8>, CommandError> {
+    let canonical_path = fs::canonicalize(path)
+        .map_err(|error| CommandError::Io(format!("Can't find {} because of {}", path, error)))?;
+    let relative_path = canonical_path.strip_prefix(toplevel()?)
+        .map_err(|_| CommandError::Usage(format!("{} is outside of the git repository", path)))?;
+    // Git expects slashes on all platforms.
+    let object_name = format!(":{}", relative_path.to_string_lossy().replace('\\', "/"));
+    run_git_bytes(&["cat-file", "blob", &object_name])
+}
+
+pub fn toplevel() -> Result<PathBuf, CommandError> {
+    let toplevel = PathBuf::from(run_git(&["rev-parse", "--show-toplevel"])?.trim_end());
+    fs::canonicalize(&toplevel)
+        .map_err(|error| CommandError::Io(format!("Can't find {} because of {}", toplevel.display(), error)))
+}
+
+pub fn run_git(args: &[&str]) -> Result<String, CommandError> {
+    Ok(String::from_utf8_lossy(&run_git_bytes(args)?).to_string())
+}
+
+fn run_git_bytes(args: &[&str]) -> Result<Vec<u8>, CommandError> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|error| CommandError::Io(format!("Can't run git because of {}", error)))?;
+    if !output.status.success() {
+        return Err(CommandError::Usage(format!("git {} failed: {}",
+                                               args[0], String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_new_lines_of_hunk_headers() {
+        assert_eq!(parse_hunk_header("@@ -12,3 +12,4 @@ Heading"), Some(12..16));
+        assert_eq!(parse_hunk_header("@@ -1 +1 @@"), Some(1..2));
+        assert_eq!(parse_hunk_header("@@ -0,0 +1,3 @@"), Some(1..4));
+    }
+
+    #[test]
+    fn deleted_lines_are_empty() {
+        assert_eq!(parse_hunk_header("@@ -5,2 +4,0 @@"), Some(4..4));
+    }
+
+    #[test]
+    fn rejects_invalid_hunk_headers() {
+        assert_eq!(parse_hunk_header("@@ -5,2 @@"), None);
+        assert_eq!(parse_hunk_header("@@ -5,2 +x,1 @@"), None);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::api::checking::{GuidanceProfile, GuidanceProfileId};
+
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Finds the guidance profile by id, display name or language (case insensitive).
+/// The error lists the available guidance profiles and similar names.
+pub fn resolve_guidance_profile(guidance_profiles: &[GuidanceProfile], selector: &str) -> Result<GuidanceProfileId, String> {
+    if let Some(profile) = guidance_profiles.iter().find(|profile| profile.id == selector) {
+        return Ok(profile.id.clone());
+    }
+
+    let normalized_selector = selector.to_lowercase();
+    let by_display_name: Vec<&GuidanceProfile> = guidance_profiles.iter()
+        .filter(|profile| profile.displayName.to_lowercase() == normalized_selector)
+        .collect();
+    let by_language: Vec<&GuidanceProfile> = guidance_profiles.iter()
+        .filter(|profile| profile.language.id.to_lowercase() == normalized_selector ||
+            profile.language.displayName.to_lowercase() == normalized_selector)
+        .collect();
+
+    let candidates = if by_display_name.is_empty() { by_language } else { by_display_name };
+    match candidates.len() {
+        1 => Ok(candidates[0].id.clone()),
+        0 => Err(unknown_guidance_profile_message(guidance_profiles, selector)),
+        _ => Err(format!("The guidance profile \"{}\" is ambiguous. Please select one of:\n{}",
+                         selector, format_guidance_profiles(&candidates))),
+    }
+}
+
+fn unknown_guidance_profile_message(guidance_profiles: &[GuidanceProfile], selector: &str) -> String {
+    let mut message = format!("Unknown guidance profile \"{}\".", selector);
+
+    let normalized_selector = selector.to_lowercase();
+    let max_distance = (selector.chars().count() / 3).max(2);
+    let mut similar: Vec<(usize, &str)> = guidance_profiles.iter()
+        .flat_map(|profile| vec![profile.id.as_str(), profile.displayName.as_str()])
+        .map(|name| (edit_distance(&normalized_selector, &name.to_lowercase()), name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    similar.sort();
+    similar.dedup_by(|a, b| a.1 == b.1);
+    if !similar.is_empty() {
+        let names: Vec<String> = similar.iter().take(MAX_SUGGESTIONS).map(|(_, name)| format!("\"{}\"", name)).collect();
+        message.push_str(&format!(" Did you mean {}?", names.join(" or ")));
+    }
+
+    let all_profiles: Vec<&GuidanceProfile> = guidance_profiles.iter().collect();
+    message.push_str(&format!("\nAvailable guidance profiles:\n{}", format_guidance_profiles(&all_profiles)));
+    message
+}
+
+fn format_guidance_profiles(guidance_profiles: &[&GuidanceProfile]) -> String {
+    let lines: Vec<String> = guidance_profiles.iter()
+        .map(|profile| format!("  {} ({}, {})", profile.id, profile.displayName, profile.language.displayName))
+        .collect();
+    lines.join("\n")
+}
+
+/// The Levenshtein distance of the characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current_row = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution_cost = if a_char == *b_char { 0 } else { 1 };
+            current_row.push((previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1));
+        }
+        previous_row = current_row;
+    }
+    previous_row[b_chars.len()]
+}
@@ -0,0 +1,155 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::atomic::AtomicBool;
+
+use console::Term;
+
+use crate::api::AcroApi;
+use crate::api::checking::{Goal, GoalId, Issue};
+use crate::commands::check::{CheckedDocument, FileCheckResult};
+use crate::commands::check::fix::{FixMode, Replacement, fix_file, suggestion_replacements};
+use crate::commands::check::output::issues::{print_issue, print_issues};
+use crate::commands::check::source_text::SourceText;
+
+const CTRL_C: char = '\u{3}';
+
+enum ReviewAction {
+    Accept(usize),
+    Skip,
+    IgnoreGoal,
+    Edit,
+    Quit,
+}
+
+/// Walks through the issues of all files and lets the user decide what to do with each issue.
+/// The accepted changes of a file are written when all its issues have been reviewed.
+pub fn review_issues(api: &AcroApi, results: &mut [FileCheckResult],
+                     goals: &HashMap<&str, &Goal>, stop_requested: &AtomicBool) -> io::Result<()> {
+    let term = Term::stdout();
+    if !term.is_term() {
+        println!("The interactive review needs a terminal, so the issues are just listed:\n");
+        print_issues(results, goals);
+        return Ok(());
+    }
+
+    let mut ignored_goals = HashSet::new();
+
+    for file_result in results.iter_mut() {
+        let (replacements, quit) = match file_result.result {
+            Ok(ref checked_document) => review_document(&term, &file_result.path, checked_document, goals, &mut ignored_goals)?,
+            Err(_) => continue,
+        };
+
+        if !replacements.is_empty() {
+            fix_file(api, file_result, &replacements, &FixMode::InPlace, stop_requested, true)?;
+        }
+
+        if quit {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the accepted replacements and whether the user wants to quit.
+fn review_document(term: &Term, path: &str, checked_document: &CheckedDocument, goals: &HashMap<&str, &Goal>,
+                   ignored_goals: &mut HashSet<GoalId>) -> io::Result<(Vec<Replacement>, bool)> {
+    let source_text = match checked_document.content.text() {
+        Some(content) => SourceText::new(content),
+        None => return Ok((vec![], false)),
+    };
+    let mut accepted: Vec<Replacement> = vec![];
+
+    for issue in &checked_document.check_result.issues {
+        if issue.readOnly || ignored_goals.contains(&issue.goalId) {
+            continue;
+        }
+
+        let span = issue_span(issue, &source_text);
+        if let Some(ref span) = span {
+            if accepted.iter().any(|r| r.overlaps(span)) {
+                continue;
+            }
+        }
+
+        print_issue(path, issue, goals.get(issue.goalId.as_str()).cloned(), Some(&source_text), true);
+        for (i, suggestion) in issue.suggestions.iter().enumerate() {
+            println!("  [{}] {}", i + 1, suggestion.surface);
+        }
+
+        loop {
+            match read_action(term, issue.suggestions.len())? {
+                ReviewAction::Accept(index) => {
+                    match suggestion_replacements(issue, &issue.suggestions[index], &source_text) {
+                        Some(replacements) => {
+                            accepted.extend(replacements);
+                            break;
+                        }
+                        None => println!("This suggestion can't be applied automatically."),
+                    }
+                }
+                ReviewAction::Skip => break,
+                ReviewAction::IgnoreGoal => {
+                    ignored_goals.insert(issue.goalId.clone());
+                    break;
+                }
+                ReviewAction::Edit => {
+                    match span {
+                        Some(ref span) => {
+                            // Issues with several matches replace the text between them too.
+                            term.write_line(&format!("Replaces \"{}\"", &source_text.text()[span.begin..span.end]))?;
+                            term.write_str("Replacement: ")?;
+                            let text = term.read_line()?;
+                            accepted.push(Replacement { text, ..span.clone() });
+                            break;
+                        }
+                        None => println!("This issue has no position in the text."),
+                    }
+                }
+                ReviewAction::Quit => return Ok((accepted, true)),
+            }
+        }
+        println!();
+    }
+
+    Ok((accepted, false))
+}
+
+/// The text from the begin of the first match to the end of the last match.
+/// Reversed matches have no span.
+fn issue_span(issue: &Issue, source_text: &SourceText) -> Option<Replacement> {
+    let matches = &issue.positionalInformation.matches;
+    if matches.iter().any(|issue_match| issue_match.originalEnd < issue_match.originalBegin) {
+        return None;
+    }
+    let begin = matches.iter().map(|issue_match| issue_match.originalBegin).min()?;
+    let end = matches.iter().map(|issue_match| issue_match.originalEnd).max()?;
+    Some(Replacement { begin: source_text.byte_offset(begin), end: source_text.byte_offset(end), text: String::new() })
+}
+
+fn read_action(term: &Term, suggestion_count: usize) -> io::Result<ReviewAction> {
+    let accept_help = match suggestion_count {
+        0 => String::new(),
+        1 => "accept (1), ".to_string(),
+        _ => format!("accept (1-{}), ", suggestion_count.min(9)),
+    };
+    loop {
+        term.write_str(&format!("{}(s)kip, (i)gnore goal, (e)dit, (q)uit: ", accept_help))?;
+        let key = term.read_char()?;
+        term.write_line(&key.to_string())?;
+        let action = match key {
+            's' => Some(ReviewAction::Skip),
+            'i' => Some(ReviewAction::IgnoreGoal),
+            'e' => Some(ReviewAction::Edit),
+            'q' | CTRL_C => Some(ReviewAction::Quit),
+            _ => key.to_digit(10)
+                .map(|digit| digit as usize)
+                .filter(|&digit| digit >= 1 && digit <= suggestion_count)
+                .map(|digit| ReviewAction::Accept(digit - 1)),
+        };
+        if let Some(action) = action {
+            return Ok(action);
+        }
+    }
+}
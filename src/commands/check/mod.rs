@@ -1,12 +1,13 @@
 use std::fs;
-use std::fs::File;
-use std::io::prelude::*;
-use std::sync::Arc;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
+use std::path::PathBuf;
 
-use glob::glob;
+use encoding_rs::Encoding;
+use glob::Pattern;
 use log::info;
 use regex::Regex;
 use threadpool::ThreadPool;
@@ -15,101 +16,445 @@ use uuid::Uuid;
 use crate::api::AcroApi;
 use crate::api::checking::{CheckRequest, DocumentInfo};
 use crate::api::checking::AggregatedReportType::{shortWithApiKey, shortWithoutApiKey};
-use crate::api::checking::CheckOptions;
+use crate::api::checking::{CheckOptions, CheckType, OffsetRange};
+use crate::api::checking::ContentEncoding;
 use crate::api::checking::CheckResultQuality;
+use crate::api::checking::CheckResult;
 use crate::api::checking::GuidanceProfileId;
+use crate::api::checking::ContentFormatId;
+use crate::api::checking::AggregatedReportLinkResponse;
 use crate::api::common_types::ApiPollResponse;
 use crate::api::errors::ApiError;
 use crate::commands::check::progress::create_multi_progress_reporter;
 use crate::commands::check::progress::ProgressReporter;
 use crate::commands::common::CommonCommandConfig;
 use crate::commands::common::connect_and_signin;
+use crate::commands::errors::CommandError;
 use crate::utils::open_url;
-use crate::api::errors::CHECK_CANCELLED_ERROR;
+use crate::commands::check::output::OutputFormat;
+use crate::commands::check::output::json::print_json_report;
+use crate::commands::check::output::junit::write_junit_report;
+use crate::commands::check::output::sarif::write_sarif_report;
+use crate::commands::check::output::issues::print_issues;
+use crate::commands::check::output::goals_by_id;
+use crate::commands::check::fix::{FixMode, fix_files};
+use crate::commands::check::interactive::review_issues;
+use crate::commands::check::quality_gate::{QualityGate, QualityGateResult};
+use crate::config::{PathGuidanceProfile, PathContentFormat, PathEncoding};
+use crate::commands::check::guidance_profile::resolve_guidance_profile;
+use crate::commands::check::content_format::{resolve_content_format, detect_content_format, is_binary_file};
+use crate::commands::check::encoding::{TextEncoding, resolve_encoding};
+use crate::commands::check::source_text::SourceText;
+use crate::commands::check::files::{FileFilter, find_files, matches_path, normalize_path};
+use crate::commands::check::git::{GitChanges, changed_files, changed_lines, filter_changed_files, staged_content};
 
 mod progress;
+pub mod output;
+pub mod quality_gate;
+pub mod source_text;
+pub mod fix;
+mod interactive;
+mod guidance_profile;
+mod content_format;
+pub mod encoding;
+mod files;
+pub mod git;
 
 pub struct CheckCommandOpts {
     pub files: Vec<String>,
+    /// Patterns of files in directories, which are checked instead of the files with default extensions.
+    pub include: Vec<String>,
+    /// Patterns of found files and directories, which are skipped.
+    pub exclude: Vec<String>,
+    /// Skips files in directories, which are ignored by .gitignore or .acrustoignore files.
+    pub use_ignore_files: bool,
+    /// Checks only the files, which git reports as changed.
+    pub git_changes: Option<GitChanges>,
+    /// Reads the files from the git index instead of the working tree, like git would commit them.
+    pub staged_content: bool,
     pub guidance_profile: Option<GuidanceProfileId>,
+    /// Overrides the guidance profile for matching files.
+    pub path_guidance_profiles: Vec<PathGuidanceProfile>,
+    /// Overrides the detection of the content format by the file extension.
+    pub content_format: Option<ContentFormatId>,
+    /// Overrides the content format for matching files.
+    pub path_content_formats: Vec<PathContentFormat>,
+    /// The encoding of text files without byte order mark (default: UTF-8).
+    pub encoding: Option<String>,
+    /// Overrides the encoding for matching files.
+    pub path_encodings: Vec<PathEncoding>,
     pub max_concurrent: usize,
     pub auth_links: bool,
+    pub output_format: OutputFormat,
+    pub quality_gate: QualityGate,
+    pub junit_report: Option<PathBuf>,
+    pub sarif_report: Option<PathBuf>,
+    pub show_issues: bool,
+    pub fix: Option<FixMode>,
+    pub interactive: bool,
 }
 
-pub fn check(config: &CommonCommandConfig, opts: &CheckCommandOpts) {
+/// The content, which has been sent to the server, together with the result of its check.
+pub struct CheckedDocument {
+    pub content: DocumentContent,
+    pub check_result: CheckResult,
+    /// The ranges of a partial check, which rechecks must use too.
+    pub partial_check_ranges: Option<Vec<OffsetRange>>,
+}
+
+pub enum DocumentContent {
+    /// The decoded text and the encoding of the file.
+    Text(String, TextEncoding),
+    /// Documents like Word or PDF files. The server extracts their text.
+    Binary(Vec<u8>),
+}
+
+impl DocumentContent {
+    /// Only text documents can be fixed or shown, because the issue offsets refer to the text.
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            DocumentContent::Text(text, _) => Some(text),
+            DocumentContent::Binary(_) => None,
+        }
+    }
+
+    pub fn text_encoding(&self) -> Option<TextEncoding> {
+        match self {
+            DocumentContent::Text(_, text_encoding) => Some(*text_encoding),
+            DocumentContent::Binary(_) => None,
+        }
+    }
+}
+
+pub struct FileCheckResult {
+    pub path: String,
+    pub reference: Option<String>,
+    /// The options of the check, which are reused for rechecks.
+    pub check_options: Arc<CheckOptions>,
+    pub result: Result<CheckedDocument, ApiError>,
+}
+
+impl FileCheckResult {
+    pub fn quality(&self) -> Result<&CheckResultQuality, &ApiError> {
+        self.result.as_ref().map(|checked_document| &checked_document.check_result.quality)
+    }
+}
+
+pub fn check(config: &CommonCommandConfig, opts: &CheckCommandOpts) -> Result<QualityGateResult, CommandError> {
     // Setup Ctrl-C handler.
     let stop_requested = Arc::new(AtomicBool::new(false));
     let stop_requested_for_handler = stop_requested.clone();
     ctrlc::set_handler(move || { stop_requested_for_handler.store(true, Ordering::SeqCst) }).expect("Error setting Ctrl-C handler");
 
-    let api = Arc::new(connect_and_signin(&config).api);
+    let machine_readable = opts.output_format != OutputFormat::Text;
+
+    // The commit is resolved once, so that an invalid one fails before anything is checked.
+    let git_changes = match opts.git_changes {
+        Some(ref git_changes) => Some(git_changes.resolve()?),
+        None => None,
+    };
+
+    // Find the files before signing in, because there might be nothing to check.
+    let files = find_check_files(opts, git_changes.as_ref())?;
+    if files.is_empty() && git_changes.is_some() {
+        let message = "No changed files to check.";
+        if machine_readable { eprintln!("{}", message) } else { println!("{}", message) }
+        return Ok(QualityGateResult::Passed);
+    }
+
+    let signin_config = CommonCommandConfig { silent: config.silent || machine_readable, machine_readable, ..config.clone() };
+    let api = Arc::new(connect_and_signin(&signin_config)?.api);
 
-    let reference_pattern = api.get_checking_capabilities().unwrap().referencePattern;
+    let capabilities = api.get_checking_capabilities()?;
+
+    // Fail fast instead of getting the same error for each file.
+    let guidance_profile = match opts.guidance_profile {
+        Some(ref selector) => Some(resolve_guidance_profile(&capabilities.guidanceProfiles, selector)
+            .map_err(CommandError::Usage)?),
+        None => None,
+    };
+    let encoding = match opts.encoding {
+        Some(ref label) => Some(resolve_encoding(label).map_err(CommandError::Usage)?),
+        None => None,
+    };
+    let content_format = match opts.content_format {
+        Some(ref selector) => Some(resolve_content_format(&capabilities.contentFormats, selector)
+            .map_err(CommandError::Usage)?),
+        None => None,
+    };
 
     // TODO: Handle referencePattern parsing error
-    let reference_regex = Regex::new(&reference_pattern);
+    let reference_regex = Regex::new(&capabilities.referencePattern);
 
     let batch_id = format!("gen.acrusto.{}", Uuid::new_v4());
-    let check_options = Arc::new(CheckOptions {
-        guidanceProfileId: opts.guidance_profile.to_owned(),
+    let check_options = CheckOptions {
+        guidanceProfileId: guidance_profile.clone(),
         batchId: Some(batch_id.clone()),
-        disableCustomFieldValidation: Some(true)
-    });
-    println!("Generated batch id: {}", batch_id);
+        disableCustomFieldValidation: Some(true),
+        contentFormat: None,
+        checkType: None,
+        partialCheckRanges: None,
+    };
+    if !machine_readable {
+        println!("Generated batch id: {}", batch_id);
+    }
+
+    let binary_supported = capabilities.contentEncodings.contains(&ContentEncoding::base64);
+
+    // Only the changed lines are checked, if their line numbers match the checked content.
+    let partial_check_changes = match git_changes {
+        Some(ref git_changes @ GitChanges::Since(_)) => Some(git_changes),
+        Some(ref git_changes @ GitChanges::Staged) if opts.staged_content => Some(git_changes),
+        _ => None,
+    };
+    let partial_check_changes = if partial_check_changes.is_some() && !capabilities.checkTypes.contains(&CheckType::partial) {
+        eprintln!("The server can't check only the changed lines, so the whole files are checked.");
+        None
+    } else {
+        partial_check_changes
+    };
+
+    let path_guidance_profiles = compile_path_mapping(
+        opts.path_guidance_profiles.iter().map(|p| (p.pattern.as_str(), p.guidance_profile.as_str())),
+        |selector| resolve_guidance_profile(&capabilities.guidanceProfiles, selector))?;
+    let path_content_formats = compile_path_mapping(
+        opts.path_content_formats.iter().map(|p| (p.pattern.as_str(), p.content_format.as_str())),
+        |selector| resolve_content_format(&capabilities.contentFormats, selector))?;
+    let path_encodings = compile_path_mapping(
+        opts.path_encodings.iter().map(|p| (p.pattern.as_str(), p.encoding.as_str())),
+        resolve_encoding)?;
 
     let pool = ThreadPool::new(opts.max_concurrent);
-    let multi_progress = create_multi_progress_reporter();
+    let multi_progress = create_multi_progress_reporter(machine_readable);
+    let results = Arc::new(Mutex::new(Vec::new()));
 
-    for file_pattern in &opts.files {
-        let found_files = glob(file_pattern).unwrap()
-            .filter_map(Result::ok)
-            .map(|path| { path.to_string_lossy().to_string() });
+    for path in files {
+        if stop_requested.load(Ordering::SeqCst) {
+            return Err(CommandError::Api(ApiError::Cancelled));
+        }
 
-        for path in found_files {
+        if let Ok(ref reference_regex) = reference_regex {
+            if reference_regex.is_match(&path) {
+                continue
+            }
+        }
+
+        let api = api.clone();
+        let check_options = Arc::new(CheckOptions {
+            guidanceProfileId: first_match(&path_guidance_profiles, &path).cloned()
+                .or_else(|| guidance_profile.clone()),
+            contentFormat: first_match(&path_content_formats, &path).cloned()
+                .or_else(|| content_format.clone())
+                .or_else(|| detect_content_format(&capabilities.contentFormats, &path)),
+            ..check_options.clone()
+        });
+
+        // The file counts as error, so that reports and the quality gate don't miss it.
+        if !binary_supported && is_binary_file(&path) {
+            let reference = document_reference(&path).ok();
+            let result = Err(ApiError::Unsupported("The server can't check binary documents.".to_string()));
+            results.lock().unwrap().push(FileCheckResult { path, reference, check_options, result });
+            continue
+        }
+
+        let file = FileToCheck {
+            encoding: first_match(&path_encodings, &path).cloned().or(encoding),
+            changed_lines: match partial_check_changes {
+                Some(git_changes) if !is_binary_file(&path) => Some(changed_lines(git_changes, &path)?),
+                _ => None,
+            },
+            staged: opts.staged_content,
+            path,
+        };
+        let multi_progress = multi_progress.clone();
+        let stop_requested = stop_requested.clone();
+        let results = results.clone();
+
+        pool.execute(move || {
             if stop_requested.load(Ordering::SeqCst) {
                 return;
             }
+            let progress_reporter = multi_progress.add(&file.path);
+            let result = check_file(&api, &check_options, &file, progress_reporter.as_ref(), &stop_requested);
+            progress_reporter.finish(result.as_ref().map(|d| &d.check_result.quality));
+            let reference = document_reference(&file.path).ok();
+            results.lock().unwrap().push(FileCheckResult { path: file.path, reference, check_options, result });
+        });
+    }
 
-            if let Ok(ref reference_regex) = reference_regex {
-                if reference_regex.is_match(&path) {
-                    continue
-                }
+    multi_progress.join();
+    pool.join();
+
+    let mut results: Vec<FileCheckResult> = results.lock().unwrap().drain(..).collect();
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if let Some(ref fix_mode) = opts.fix {
+        if let Err(error) = fix_files(&api, &mut results, fix_mode, &stop_requested, !machine_readable) {
+            eprintln!("Can't fix files because of {}", error);
+        }
+    }
+
+    // The results of the files are more important than the link to the dashboard.
+    let aggregated_report_links = match api.get_link_to_aggregated_report(&batch_id) {
+        Ok(aggregated_report_links) => Some(aggregated_report_links),
+        Err(error) => {
+            eprintln!("Can't get the link to the Content Analysis Dashboard because of {}", error);
+            None
+        }
+    };
+    info!("report_links = {:?}", aggregated_report_links);
+
+    let goals = goals_by_id(&capabilities.guidanceProfiles, guidance_profile.as_deref());
+
+    if opts.interactive && !machine_readable {
+        if let Err(error) = review_issues(&api, &mut results, &goals, &stop_requested) {
+            eprintln!("Can't review issues because of {}", error);
+        }
+    } else if opts.show_issues && !machine_readable {
+        print_issues(&results, &goals);
+    }
+
+    match opts.output_format {
+        OutputFormat::Text => if let Some(ref aggregated_report_links) = aggregated_report_links {
+            if let Err(error) = show_aggregated_report(&config, opts, aggregated_report_links) {
+                eprintln!("{}", error);
             }
+        },
+        OutputFormat::Json => print_json_report(&batch_id, &results,
+                                                aggregated_report_links.as_ref().map_or(&[], |links| &links.reports)),
+    }
 
-            let api = api.clone();
-            let check_options = check_options.clone();
-            let multi_progress = multi_progress.clone();
-            let stop_requested = stop_requested.clone();
+    if let Some(ref junit_report) = opts.junit_report {
+        if let Err(error) = write_junit_report(junit_report, &batch_id, &results) {
+            eprintln!("Can't write JUnit report \"{}\" because of {}", junit_report.display(), error);
+        }
+    }
 
-            pool.execute(move || {
-                if stop_requested.load(Ordering::SeqCst) {
-                    return;
-                }
-                let progress_reporter = multi_progress.add(&path);
-                let result = check_file(&api, &check_options, &path, progress_reporter.as_ref(), &stop_requested);
-                progress_reporter.finish(&result);
-            });
+    if let Some(ref sarif_report) = opts.sarif_report {
+        let guidance_profile_id = guidance_profile.as_deref();
+        if let Err(error) = write_sarif_report(sarif_report, &batch_id, &results,
+                                               &capabilities.guidanceProfiles, guidance_profile_id) {
+            eprintln!("Can't write SARIF report \"{}\" because of {}", sarif_report.display(), error);
         }
     }
 
-    multi_progress.join();
-    pool.join();
+    let (quality_gate_result, violations) = opts.quality_gate.evaluate(&results);
+    if !violations.is_empty() {
+        eprintln!("Quality gate failed:");
+        for violation in violations {
+            eprintln!("  {}", violation);
+        }
+    }
+    Ok(quality_gate_result)
+}
+
+/// Without explicit files, the changed files of the whole working directory are checked.
+fn find_check_files(opts: &CheckCommandOpts, git_changes: Option<&GitChanges>) -> Result<Vec<String>, CommandError> {
+    let filter = FileFilter::new(&opts.include, &opts.exclude, opts.use_ignore_files)?;
+    match git_changes {
+        Some(git_changes) => {
+            let changed_files = changed_files(git_changes)?;
+            let file_patterns = if opts.files.is_empty() { vec![".".to_string()] } else { opts.files.clone() };
+            Ok(filter_changed_files(find_files(&file_patterns, filter)?, &changed_files))
+        }
+        None => find_files(&opts.files, filter),
+    }
+}
 
-    show_aggregated_report(&config, opts, &api, &batch_id);
+/// Compiles the patterns of the config and resolves their values.
+fn compile_path_mapping<'a, I, F, T>(entries: I, resolve: F) -> Result<Vec<(Pattern, T)>, CommandError>
+    where I: Iterator<Item=(&'a str, &'a str)>,
+          F: Fn(&str) -> Result<T, String>
+{
+    entries.map(|(pattern, value)| {
+        let compiled_pattern = Pattern::new(normalize_path(pattern))
+            .map_err(|error| CommandError::Config(format!("Invalid pattern \"{}\": {}", pattern, error)))?;
+        let resolved_value = resolve(value)
+            .map_err(|message| CommandError::Config(format!("Files matching \"{}\": {}", pattern, message)))?;
+        Ok((compiled_pattern, resolved_value))
+    }).collect()
 }
 
-pub fn check_file(api: &AcroApi, check_options: &CheckOptions, filename: &str,
+fn first_match<'a, T>(path_mapping: &'a [(Pattern, T)], path: &str) -> Option<&'a T> {
+    path_mapping.iter().find(|(pattern, _)| matches_path(pattern, path)).map(|(_, value)| value)
+}
+
+fn document_reference(filename: &str) -> std::io::Result<String> {
+    Ok(fs::canonicalize(filename)?.to_string_lossy().into_owned())
+}
+
+/// A file and how to read and check it.
+pub struct FileToCheck {
+    pub path: String,
+    /// Text files are decoded with this encoding unless they start with a byte order mark.
+    pub encoding: Option<&'static Encoding>,
+    /// Only these 1-based lines are checked, but the whole file is sent as context.
+    pub changed_lines: Option<Vec<Range<usize>>>,
+    /// Reads the content from the git index.
+    pub staged: bool,
+}
+
+pub fn check_file(api: &AcroApi, check_options: &CheckOptions, file: &FileToCheck,
                   progress_reporter: &ProgressReporter,
-                  stop_requested: &AtomicBool) -> Result<CheckResultQuality, ApiError> {
-    let mut f = File::open(filename)?;
-    let mut file_content = String::new();
-    f.read_to_string(&mut file_content).expect("Problem reading document");
+                  stop_requested: &AtomicBool) -> Result<CheckedDocument, ApiError> {
+    let bytes = if file.staged {
+        staged_content(&file.path).map_err(|error| ApiError::Io(error.to_string()))?
+    } else {
+        fs::read(&file.path)?
+    };
+    let content = if is_binary_file(&file.path) {
+        DocumentContent::Binary(bytes)
+    } else {
+        let (text, text_encoding) = TextEncoding::decode(&bytes, file.encoding)
+            .map_err(ApiError::Encoding)?;
+        DocumentContent::Text(text, text_encoding)
+    };
+
+    let partial_check_options;
+    let check_options = match (file.changed_lines.as_deref(), content.text()) {
+        // Files with only deleted lines are checked completely.
+        (Some(changed_lines), Some(text)) if !changed_lines.is_empty() => {
+            partial_check_options = with_partial_check_ranges(check_options, partial_check_ranges(text, changed_lines));
+            &partial_check_options
+        }
+        _ => check_options,
+    };
 
+    check_content(api, check_options, content, &document_reference(&file.path)?, progress_reporter, stop_requested)
+}
+
+pub fn with_partial_check_ranges(check_options: &CheckOptions, ranges: Vec<OffsetRange>) -> CheckOptions {
+    CheckOptions {
+        checkType: Some(CheckType::partial),
+        partialCheckRanges: Some(ranges),
+        ..check_options.clone()
+    }
+}
+
+/// Converts the 1-based lines to the offsets of the API, which include the line breaks.
+fn partial_check_ranges(text: &str, lines: &[Range<usize>]) -> Vec<OffsetRange> {
+    let source_text = SourceText::new(text);
+    lines.iter()
+        .map(|lines| OffsetRange {
+            begin: source_text.api_offset_of_line(lines.start),
+            end: source_text.api_offset_of_line(lines.end),
+        })
+        .collect()
+}
+
+pub fn check_content(api: &AcroApi, check_options: &CheckOptions, content: DocumentContent, reference: &str,
+                     progress_reporter: &ProgressReporter,
+                     stop_requested: &AtomicBool) -> Result<CheckedDocument, ApiError> {
+    let (request_content, content_encoding) = match content {
+        DocumentContent::Text(ref text, _) => (text.clone(), None),
+        DocumentContent::Binary(ref bytes) => (base64::encode(bytes), Some(ContentEncoding::base64)),
+    };
     let check_request = CheckRequest {
-        content: file_content,
+        content: request_content,
+        contentEncoding: content_encoding,
         checkOptions: check_options.clone(),
         document: Some(DocumentInfo {
-            reference: Some(fs::canonicalize(filename)?.to_string_lossy().into_owned())
+            reference: Some(reference.to_string())
         }),
     };
     let check = api.check(&check_request)?;
@@ -119,7 +464,7 @@ pub fn check_file(api: &AcroApi, check_options: &CheckOptions, filename: &str,
     loop {
         if stop_requested.load(Ordering::SeqCst) {
              api.cancel_check(&check.links)?;
-             return Err(CHECK_CANCELLED_ERROR.clone());
+             return Err(ApiError::Cancelled);
         }
 
         check_poll_response = api.get_checking_result(&check.links)?;
@@ -139,18 +484,17 @@ pub fn check_file(api: &AcroApi, check_options: &CheckOptions, filename: &str,
         }
     }
 
-    Ok(check_result.quality)
+    let partial_check_ranges = check_options.partialCheckRanges.clone();
+    Ok(CheckedDocument { content, check_result, partial_check_ranges })
 }
 
-fn show_aggregated_report(config: &CommonCommandConfig, opts: &CheckCommandOpts, api: &AcroApi,
-                          batch_id: &str) {
-    let aggregated_report_links = api.get_link_to_aggregated_report(&batch_id).unwrap();
-    info!("report_links = {:?}", aggregated_report_links);
-
+fn show_aggregated_report(config: &CommonCommandConfig, opts: &CheckCommandOpts,
+                          aggregated_report_links: &AggregatedReportLinkResponse) -> Result<(), CommandError> {
     let report_type = if opts.auth_links { shortWithApiKey } else { shortWithoutApiKey };
 
     let aggregated_report_link = aggregated_report_links.reports.iter()
-        .find(|report| report.reportType == report_type).unwrap();
+        .find(|report| report.reportType == report_type)
+        .ok_or_else(|| CommandError::Config(format!("The server provides no {:?} report.", report_type)))?;
 
     if !config.silent {
         println!("Find the Content Analysis Dashboard for your files here:")
@@ -158,6 +502,10 @@ fn show_aggregated_report(config: &CommonCommandConfig, opts: &CheckCommandOpts,
     println!("{} ", aggregated_report_link.link);
 
     if config.open_url {
-        open_url(&aggregated_report_link.link).unwrap();
+        if let Err(error) = open_url(&aggregated_report_link.link) {
+            eprintln!("{}", error);
+        }
     }
+
+    Ok(())
 }
\ No newline at end of file
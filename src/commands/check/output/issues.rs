@@ -0,0 +1,119 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ansi_term::Colour::{Blue, Yellow};
+use ansi_term::{Colour, Style};
+use console::Term;
+
+use crate::api::checking::{Goal, Issue};
+use crate::commands::check::FileCheckResult;
+use crate::commands::check::source_text::SourceText;
+
+const MAX_SHOWN_SUGGESTIONS: usize = 3;
+
+/// Prints the issues of all files in the style of rustc:
+///
+/// ```text
+/// Goal: Display surface of the issue
+///   --> path/file.md:3:5
+///    |
+///  3 | The offending text
+///    |     ^^^^^^^^^
+///    = suggestions: better, text
+/// ```
+pub fn print_issues(results: &[FileCheckResult], goals: &HashMap<&str, &Goal>) {
+    let colored = Term::stdout().is_term();
+    for file_result in results {
+        if let Ok(ref checked_document) = file_result.result {
+            let source_text = checked_document.content.text().map(SourceText::new);
+            for issue in &checked_document.check_result.issues {
+                let goal = goals.get(issue.goalId.as_str()).cloned();
+                print_issue(&file_result.path, issue, goal, source_text.as_ref(), colored);
+            }
+        }
+    }
+}
+
+/// Binary documents have no `source_text`, so only the location of the file is shown.
+pub fn print_issue(path: &str, issue: &Issue, goal: Option<&Goal>, source_text: Option<&SourceText>, colored: bool) {
+    let goal_style = if colored { goal_colour(goal).bold() } else { Style::new() };
+    let gutter_style = if colored { Blue.bold() } else { Style::new() };
+
+    let goal_name = goal.map(|g| g.displayName.as_str()).unwrap_or(&issue.goalId);
+    println!("{}: {}", goal_style.paint(goal_name), issue.displaySurface);
+
+    let underlines = source_text.map(|source_text| underlines_by_line(issue, source_text)).unwrap_or_default();
+    let gutter_width = underlines.keys().last().map(|line| line.to_string().len()).unwrap_or(1);
+    let empty_gutter = " ".repeat(gutter_width);
+
+    match underlines.iter().next() {
+        Some((line, ranges)) => {
+            println!("{}{} {}:{}:{}", empty_gutter, gutter_style.paint("-->"), path, line, ranges[0].0);
+        }
+        None => {
+            println!("{}{} {}", empty_gutter, gutter_style.paint("-->"), path);
+        }
+    }
+
+    if !underlines.is_empty() {
+        println!("{} {}", empty_gutter, gutter_style.paint("|"));
+    }
+    for (line, ranges) in &underlines {
+        let line_text = source_text.map(|source_text| source_text.line(*line)).unwrap_or_default().replace('\t', " ");
+        let line_number = format!("{:>width$}", line, width = gutter_width);
+        println!("{} {} {}", gutter_style.paint(line_number), gutter_style.paint("|"), line_text);
+        println!("{} {} {}", empty_gutter, gutter_style.paint("|"), goal_style.paint(underline(ranges)));
+    }
+
+    if !issue.suggestions.is_empty() {
+        let suggestions: Vec<&str> = issue.suggestions.iter()
+            .take(MAX_SHOWN_SUGGESTIONS)
+            .map(|s| s.surface.as_str())
+            .collect();
+        println!("{} {} suggestions: {}", empty_gutter, gutter_style.paint("="), suggestions.join(", "));
+    }
+    println!();
+}
+
+/// Returns the 1-based columns [from, to) which should be underlined for each line.
+fn underlines_by_line(issue: &Issue, source_text: &SourceText) -> BTreeMap<usize, Vec<(usize, usize)>> {
+    let mut underlines: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+    for issue_match in &issue.positionalInformation.matches {
+        let start = source_text.position(issue_match.originalBegin);
+        let end = source_text.position(issue_match.originalEnd);
+        for line in start.line..=end.line {
+            let from = if line == start.line { start.column } else { 1 };
+            let to = if line == end.line { end.column } else { source_text.line(line).chars().count() + 1 };
+            underlines.entry(line).or_default().push((from, to.max(from + 1)));
+        }
+    }
+    underlines
+}
+
+fn underline(ranges: &[(usize, usize)]) -> String {
+    let mut sorted_ranges = ranges.to_vec();
+    sorted_ranges.sort();
+    let mut underline = String::new();
+    for (from, to) in sorted_ranges {
+        let current_len = underline.chars().count() + 1;
+        if from > current_len {
+            underline.push_str(&" ".repeat(from - current_len));
+        }
+        let already_underlined = current_len.max(from) - from;
+        underline.push_str(&"^".repeat((to - from).saturating_sub(already_underlined)));
+    }
+    underline
+}
+
+/// The goal colors of the guidance profile are hex colors like "#d0021b".
+fn goal_colour(goal: Option<&Goal>) -> Colour {
+    goal.and_then(|g| parse_hex_colour(&g.color)).unwrap_or(Yellow)
+}
+
+fn parse_hex_colour(color: &str) -> Option<Colour> {
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Colour::RGB(component(0)?, component(2)?, component(4)?))
+}
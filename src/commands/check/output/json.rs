@@ -0,0 +1,60 @@
+use serde_derive::Serialize;
+
+use crate::api::checking::AggregatedReportLink;
+use crate::api::checking::{CheckResultQuality, CheckResultCounts, CheckResultGoal, CheckResultReports};
+use crate::api::checking::{Issue, Keywords};
+use crate::api::errors::ApiError;
+use crate::commands::check::FileCheckResult;
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Debug)]
+struct JsonCheckReport<'a> {
+    batchId: &'a str,
+    files: Vec<JsonFileCheckResult<'a>>,
+    reports: &'a [AggregatedReportLink],
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Debug)]
+struct JsonFileCheckResult<'a> {
+    path: &'a str,
+    reference: Option<&'a str>,
+    /// The encoding of text files.
+    encoding: Option<&'static str>,
+    quality: Option<&'a CheckResultQuality>,
+    error: Option<&'a ApiError>,
+    counts: Option<&'a CheckResultCounts>,
+    goals: Option<&'a [CheckResultGoal]>,
+    issues: Option<&'a [Issue]>,
+    keywords: Option<&'a Keywords>,
+    reports: Option<&'a CheckResultReports>,
+}
+
+impl<'a> From<&'a FileCheckResult> for JsonFileCheckResult<'a> {
+    fn from(file_result: &'a FileCheckResult) -> Self {
+        let check_result = file_result.result.as_ref().ok().map(|d| &d.check_result);
+        JsonFileCheckResult {
+            path: &file_result.path,
+            reference: file_result.reference.as_deref(),
+            encoding: file_result.result.as_ref().ok()
+                .and_then(|d| d.content.text_encoding())
+                .map(|text_encoding| text_encoding.name()),
+            quality: file_result.quality().ok(),
+            error: file_result.quality().err(),
+            counts: check_result.and_then(|r| r.counts.as_ref()),
+            goals: check_result.map(|r| r.goals.as_slice()),
+            issues: check_result.map(|r| r.issues.as_slice()),
+            keywords: check_result.and_then(|r| r.keywords.as_ref()),
+            reports: check_result.map(|r| &r.reports),
+        }
+    }
+}
+
+pub fn print_json_report(batch_id: &str, results: &[FileCheckResult], report_links: &[AggregatedReportLink]) {
+    let report = JsonCheckReport {
+        batchId: batch_id,
+        files: results.iter().map(JsonFileCheckResult::from).collect(),
+        reports: report_links,
+    };
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
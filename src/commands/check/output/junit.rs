@@ -0,0 +1,119 @@
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+use crate::api::checking::QualityStatus;
+use crate::commands::check::{CheckedDocument, FileCheckResult};
+use crate::commands::check::source_text::SourceText;
+
+const TEST_SUITE_NAME: &str = "acrolinx";
+
+/// Writes every checked file as test case: red and yellow results become failures,
+/// files which could not be checked become errors.
+pub fn write_junit_report(report_path: &Path, batch_id: &str, results: &[FileCheckResult]) -> io::Result<()> {
+    let mut file = File::create(report_path)?;
+    file.write_all(create_junit_report(batch_id, results).as_bytes())
+}
+
+fn create_junit_report(batch_id: &str, results: &[FileCheckResult]) -> String {
+    let failures = results.iter()
+        .filter(|r| r.quality().map(|q| is_failure(&q.status)).unwrap_or(false))
+        .count();
+    let errors = results.iter().filter(|r| r.result.is_err()).count();
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!("<testsuites tests=\"{}\" failures=\"{}\" errors=\"{}\">\n", results.len(), failures, errors));
+    xml.push_str(&format!("  <testsuite name=\"{}\" id=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+                          TEST_SUITE_NAME, escape_xml(batch_id), results.len(), failures, errors));
+
+    for file_result in results {
+        xml.push_str(&format!("    <testcase name=\"{}\" classname=\"{}\"",
+                              escape_xml(&file_result.path), TEST_SUITE_NAME));
+        match file_result.result {
+            Ok(ref checked_document) if is_failure(&checked_document.check_result.quality.status) => {
+                let quality = &checked_document.check_result.quality;
+                let message = format!("Score {} ({:?})", quality.score, quality.status);
+                xml.push_str(">\n");
+                xml.push_str(&format!("      <failure message=\"{}\" type=\"{:?}\">{}</failure>\n",
+                                      escape_xml(&message), quality.status, escape_xml(&format_issues(checked_document))));
+                xml.push_str("    </testcase>\n");
+            }
+            Ok(_) => {
+                xml.push_str("/>\n");
+            }
+            Err(ref error) => {
+                xml.push_str(">\n");
+                xml.push_str(&format!("      <error message=\"{}\" type=\"{}\">{}</error>\n",
+                                      escape_xml(error.title()), escape_xml(error.error_type()), escape_xml(error.detail())));
+                xml.push_str("    </testcase>\n");
+            }
+        }
+    }
+
+    xml.push_str("  </testsuite>\n");
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// One line per issue: "line:column goal: surface (suggestion, ...)"
+fn format_issues(checked_document: &CheckedDocument) -> String {
+    let source_text = checked_document.content.text().map(SourceText::new);
+    checked_document.check_result.issues.iter().map(|issue| {
+        let position = issue.positionalInformation.matches.first()
+            .and_then(|first_match| source_text.as_ref().map(|source_text| source_text.position(first_match.originalBegin)));
+        let mut line = match position {
+            Some(position) => format!("{}:{} ", position.line, position.column),
+            None => String::new(),
+        };
+        line.push_str(&format!("{}: {}", issue.goalId, issue.displaySurface));
+        if !issue.suggestions.is_empty() {
+            let suggestions: Vec<&str> = issue.suggestions.iter().map(|s| s.surface.as_str()).collect();
+            line.push_str(&format!(" ({})", suggestions.join(", ")));
+        }
+        line
+    }).collect::<Vec<_>>().join("\n")
+}
+
+fn is_failure(status: &QualityStatus) -> bool {
+    match status {
+        QualityStatus::red | QualityStatus::yellow => true,
+        QualityStatus::green => false,
+    }
+}
+
+/// XML 1.0 can't contain control characters except tab and line breaks, not even as character references,
+/// so they are replaced by U+FFFD.
+fn escape_xml(text: &str) -> String {
+    text.chars().fold(String::with_capacity(text.len()), |mut escaped, c| {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\u{0}'..='\u{1F}' | '\u{FFFE}' | '\u{FFFF}' => escaped.push(char::REPLACEMENT_CHARACTER),
+            _ => escaped.push(c),
+        }
+        escaped
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_markup_characters() {
+        assert_eq!(escape_xml(r#"<a href="x">Tom & Jerry's</a>"#),
+                   "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;");
+    }
+
+    #[test]
+    fn replaces_control_characters() {
+        assert_eq!(escape_xml("a\u{0}b\u{8}c\u{1B}[0m\u{FFFF}"), "a\u{FFFD}b\u{FFFD}c\u{FFFD}[0m\u{FFFD}");
+        assert_eq!(escape_xml("tab\tline\r\nä😀"), "tab\tline\r\nä😀");
+    }
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::api::checking::{GuidanceProfile, Goal};
+
+pub mod json;
+pub mod junit;
+pub mod sarif;
+pub mod issues;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub const VALUES: &'static [&'static str] = &["text", "json"];
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("Unknown output format \"{}\"", s))
+        }
+    }
+}
+
+/// Goals of the selected guidance profile win over goals with the same id in other profiles.
+pub fn goals_by_id<'a>(guidance_profiles: &'a [GuidanceProfile], guidance_profile_id: Option<&str>) -> HashMap<&'a str, &'a Goal> {
+    let mut goals = HashMap::new();
+    let (selected, others): (Vec<&GuidanceProfile>, Vec<&GuidanceProfile>) = guidance_profiles.iter()
+        .partition(|profile| Some(profile.id.as_str()) == guidance_profile_id);
+    for profile in others.into_iter().chain(selected) {
+        for goal in &profile.goals {
+            goals.insert(goal.id.as_str(), goal);
+        }
+    }
+    goals
+}
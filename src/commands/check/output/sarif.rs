@@ -0,0 +1,213 @@
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::crate_version;
+use serde_json::{json, Value};
+
+use crate::api::checking::{GuidanceProfile, Issue};
+use crate::commands::check::FileCheckResult;
+use crate::commands::check::files::absolute_path;
+use crate::commands::check::git::toplevel;
+use crate::commands::check::source_text::SourceText;
+use crate::commands::check::encoding::TextEncoding;
+use crate::commands::check::output::goals_by_id;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+/// The base of the relative artifact URIs, like code scanning tools expect it.
+const SOURCE_ROOT_ID: &str = "%SRCROOT%";
+
+/// Writes a SARIF 2.1 log, which can be uploaded to code scanning tools like GitHub.
+/// Every issue becomes a result and its goal becomes the rule.
+pub fn write_sarif_report(report_path: &Path, batch_id: &str, results: &[FileCheckResult],
+                          guidance_profiles: &[GuidanceProfile], guidance_profile_id: Option<&str>) -> io::Result<()> {
+    let sarif_log = create_sarif_log(batch_id, results, guidance_profiles, guidance_profile_id);
+    serde_json::to_writer_pretty(File::create(report_path)?, &sarif_log).map_err(io::Error::from)
+}
+
+fn create_sarif_log(batch_id: &str, results: &[FileCheckResult],
+                    guidance_profiles: &[GuidanceProfile], guidance_profile_id: Option<&str>) -> Value {
+    let goals = goals_by_id(guidance_profiles, guidance_profile_id);
+    let source_root = source_root();
+
+    // The rule ids with the help link of their first issue.
+    let mut rules: Vec<(&str, Option<&str>)> = vec![];
+    let mut sarif_results = vec![];
+    let mut notifications = vec![];
+
+    for file_result in results {
+        let artifact_location = artifact_location(&file_result.path, source_root.as_deref());
+        match file_result.result {
+            Ok(ref checked_document) => {
+                let source_text = checked_document.content.text().map(SourceText::new);
+                let text_encoding = checked_document.content.text_encoding();
+                for issue in &checked_document.check_result.issues {
+                    let rule_index = match rules.iter().position(|(id, _)| *id == issue.goalId) {
+                        Some(index) => index,
+                        None => {
+                            rules.push((&issue.goalId, issue.links.help.as_deref()));
+                            rules.len() - 1
+                        }
+                    };
+                    if rules[rule_index].1.is_none() {
+                        rules[rule_index].1 = issue.links.help.as_deref();
+                    }
+                    sarif_results.push(json!({
+                        "ruleId": issue.goalId,
+                        "ruleIndex": rule_index,
+                        "level": "warning",
+                        "message": { "text": issue_message(issue) },
+                        "locations": [issue_location(&artifact_location, issue, source_text.as_ref().zip(text_encoding))],
+                    }));
+                }
+            }
+            Err(ref error) => {
+                notifications.push(json!({
+                    "level": "error",
+                    "message": { "text": format!("{}: {}", error.title(), error.detail()) },
+                    "locations": [{ "physicalLocation": { "artifactLocation": artifact_location } }],
+                }));
+            }
+        }
+    }
+
+    let rules: Vec<Value> = rules.iter().map(|(rule_id, help_link)| {
+        let display_name = goals.get(rule_id).map(|goal| goal.displayName.as_str()).unwrap_or(rule_id);
+        let mut rule = json!({
+            "id": rule_id,
+            "shortDescription": { "text": display_name },
+        });
+        if let Some(help_link) = help_link {
+            rule["helpUri"] = json!(help_link);
+        }
+        rule
+    }).collect();
+
+    let mut run = json!({
+        "tool": {
+            "driver": {
+                "name": "acrusto",
+                "version": crate_version!(),
+                "rules": rules,
+            }
+        },
+        "automationDetails": { "id": batch_id },
+        "columnKind": "unicodeCodePoints",
+        "invocations": [{
+            "executionSuccessful": notifications.is_empty(),
+            "toolExecutionNotifications": notifications,
+        }],
+        "results": sarif_results,
+    });
+    if let Some(source_root) = source_root {
+        // The base URI must end with a slash.
+        run["originalUriBaseIds"] = json!({ SOURCE_ROOT_ID: { "uri": file_uri(&source_root) + "/" } });
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [run]
+    })
+}
+
+fn issue_message(issue: &Issue) -> String {
+    if issue.suggestions.is_empty() {
+        issue.displaySurface.clone()
+    } else {
+        let suggestions: Vec<&str> = issue.suggestions.iter().map(|s| s.surface.as_str()).collect();
+        format!("{} (Suggestions: {})", issue.displaySurface, suggestions.join(", "))
+    }
+}
+
+/// Binary documents have no source text, so their issues have no region.
+/// The byte offsets refer to the file, which might not be encoded in UTF-8.
+fn issue_location(artifact_location: &Value, issue: &Issue, source: Option<(&SourceText, TextEncoding)>) -> Value {
+    let matches = &issue.positionalInformation.matches;
+    match (matches.first(), matches.last(), source) {
+        (Some(first_match), Some(last_match), Some((source_text, text_encoding))) => {
+            let start = source_text.position(first_match.originalBegin);
+            let end = source_text.position(last_match.originalEnd);
+            let byte_offset = text_encoding.original_byte_offset(source_text.text(), source_text.byte_offset(first_match.originalBegin));
+            let end_byte_offset = text_encoding.original_byte_offset(source_text.text(), source_text.byte_offset(last_match.originalEnd));
+            json!({
+                "physicalLocation": {
+                    "artifactLocation": artifact_location,
+                    "region": {
+                        "startLine": start.line,
+                        "startColumn": start.column,
+                        "endLine": end.line,
+                        "endColumn": end.column,
+                        "byteOffset": byte_offset,
+                        "byteLength": end_byte_offset.saturating_sub(byte_offset),
+                    }
+                }
+            })
+        }
+        _ => json!({ "physicalLocation": { "artifactLocation": artifact_location } })
+    }
+}
+
+/// The git repository or else the current directory.
+fn source_root() -> Option<PathBuf> {
+    toplevel().ok().or_else(|| env::current_dir().and_then(fs::canonicalize).ok())
+}
+
+/// Files in the source root get a URI relative to it, other files an absolute file URI.
+fn artifact_location(path: &str, source_root: Option<&Path>) -> Value {
+    let absolute_path = match fs::canonicalize(path).ok().or_else(|| absolute_path(path)) {
+        Some(absolute_path) => absolute_path,
+        None => return json!({ "uri": encode_uri_path(path) }),
+    };
+    match source_root.and_then(|source_root| absolute_path.strip_prefix(source_root).ok()) {
+        Some(relative_path) => json!({ "uri": encode_uri_path(&relative_path.to_string_lossy()), "uriBaseId": SOURCE_ROOT_ID }),
+        None => json!({ "uri": file_uri(&absolute_path) }),
+    }
+}
+
+fn file_uri(absolute_path: &Path) -> String {
+    let uri_path = encode_uri_path(&absolute_path.to_string_lossy());
+    // Windows paths like "C:/docs" need an additional slash.
+    if uri_path.starts_with('/') {
+        format!("file://{}", uri_path)
+    } else {
+        format!("file:///{}", uri_path)
+    }
+}
+
+/// Percent-encodes all bytes of the path except unreserved characters and slashes.
+fn encode_uri_path(path: &str) -> String {
+    let mut uri_path = String::with_capacity(path.len());
+    for byte in path.replace('\\', "/").bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri_path.push(byte as char),
+            _ => uri_path.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri_path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_encodes_uri_paths() {
+        assert_eq!(encode_uri_path("docs/a-b_c.~1.md"), "docs/a-b_c.~1.md");
+        assert_eq!(encode_uri_path("my docs/#1 ä.md"), "my%20docs/%231%20%C3%A4.md");
+        assert_eq!(encode_uri_path("docs\\a.md"), "docs/a.md");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn makes_paths_relative_to_the_source_root() {
+        let source_root = env::current_dir().unwrap();
+        assert_eq!(artifact_location("./src/main.rs", Some(&source_root)),
+                   json!({ "uri": "src/main.rs", "uriBaseId": SOURCE_ROOT_ID }));
+        assert_eq!(artifact_location("/no such dir/a.md", Some(&source_root)),
+                   json!({ "uri": "file:///no%20such%20dir/a.md" }));
+    }
+}
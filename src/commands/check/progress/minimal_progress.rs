@@ -2,7 +2,6 @@ use crate::api::checking::CheckResultQuality;
 use crate::commands::check::progress::ProgressReporter;
 use crate::commands::check::progress::MultiProgressReporter;
 use crate::api::errors::ApiError;
-use crate::api::errors::CHECK_CANCELLED_ERROR_TYPE;
 
 pub struct MinimalProgressReporter {
     path: String
@@ -11,17 +10,22 @@ pub struct MinimalProgressReporter {
 impl ProgressReporter for MinimalProgressReporter {
     fn set_progress(&self, _percent: f64) {}
 
-    fn finish(&self, result: &Result<CheckResultQuality, ApiError>) {
+    fn finish(&self, result: Result<&CheckResultQuality, &ApiError>) {
         match result {
             Ok(quality) => {
                 println!("Check done for: {} {}", self.path, quality.score);
             }
+            Err(ApiError::Cancelled) => {
+                println!("Check cancelled: {}", self.path);
+            }
+            Err(ApiError::Timeout(detail)) => {
+                println!("Timeout in {}: {}", self.path, detail);
+            }
+            Err(ApiError::AuthExpired(_)) => {
+                println!("Authentication expired in {}: Please sign in again.", self.path);
+            }
             Err(error) => {
-                if error._type == CHECK_CANCELLED_ERROR_TYPE {
-                    println!("Check cancelled: {}", self.path);
-                } else {
-                    println!("Error in {}: {}({})", self.path, error.title, error.detail);
-                }
+                println!("Error in {}: {}({})", self.path, error.title(), error.detail());
             }
         }
     }
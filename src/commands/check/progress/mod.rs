@@ -4,13 +4,15 @@ use std::sync::Arc;
 use console::Term;
 use crate::commands::check::progress::minimal_progress::MinimalMultiProgressReporter;
 use crate::api::errors::ApiError;
+use crate::commands::check::progress::silent_progress::SilentMultiProgressReporter;
 
 mod progress_bar;
 mod minimal_progress;
+pub mod silent_progress;
 
 pub trait ProgressReporter {
     fn set_progress(&self, percent: f64);
-    fn finish(&self, result: &Result<CheckResultQuality, ApiError>);
+    fn finish(&self, result: Result<&CheckResultQuality, &ApiError>);
 }
 
 pub trait MultiProgressReporter: Sync + Send {
@@ -18,10 +20,14 @@ pub trait MultiProgressReporter: Sync + Send {
     fn join(&self) {}
 }
 
-pub fn create_multi_progress_reporter() -> Arc<MultiProgressReporter> {
+/// The progress bars are drawn on stderr, so they are kept even if stdout is reserved for
+/// machine readable output (`quiet`).
+pub fn create_multi_progress_reporter(quiet: bool) -> Arc<MultiProgressReporter> {
     let term = Term::stdout();
     if term.is_term() {
         Arc::new(MultiProgressBarReporter::new())
+    } else if quiet {
+        Arc::new(SilentMultiProgressReporter {})
     } else {
         Arc::new(MinimalMultiProgressReporter{})
     }
@@ -10,7 +10,6 @@ use ansi_term::ANSIGenericString;
 use crate::commands::check::progress::ProgressReporter;
 use crate::commands::check::progress::MultiProgressReporter;
 use crate::api::errors::ApiError;
-use crate::api::errors::CHECK_CANCELLED_ERROR_TYPE;
 
 pub struct ProgressBarReporter {
     progress_bar: indicatif::ProgressBar,
@@ -40,15 +39,13 @@ impl ProgressReporter for ProgressBarReporter {
         self.progress_bar.set_position(percent.round() as u64);
     }
 
-    fn finish(&self, result: &Result<CheckResultQuality, ApiError>) {
+    fn finish(&self, result: Result<&CheckResultQuality, &ApiError>) {
         let message = match result {
             Ok(quality) => colored_score(quality),
-            Err(error) =>
-                if error._type == CHECK_CANCELLED_ERROR_TYPE {
-                    Red.paint("CNL")
-                } else {
-                    Red.blink().paint("ERR")
-                }
+            Err(ApiError::Cancelled) => Red.paint("CNL"),
+            Err(ApiError::Timeout(_)) => Red.paint("TMO"),
+            Err(ApiError::AuthExpired(_)) => Red.paint("AUT"),
+            Err(_) => Red.blink().paint("ERR"),
         };
         self.progress_bar.finish_with_message(&format!("{}", message));
     }
@@ -0,0 +1,21 @@
+use crate::api::checking::CheckResultQuality;
+use crate::commands::check::progress::ProgressReporter;
+use crate::commands::check::progress::MultiProgressReporter;
+use crate::api::errors::ApiError;
+
+/// Used for machine readable output, which must not be mixed with progress messages.
+pub struct SilentProgressReporter {}
+
+impl ProgressReporter for SilentProgressReporter {
+    fn set_progress(&self, _percent: f64) {}
+
+    fn finish(&self, _result: Result<&CheckResultQuality, &ApiError>) {}
+}
+
+pub struct SilentMultiProgressReporter {}
+
+impl MultiProgressReporter for SilentMultiProgressReporter {
+    fn add(&self, _path: &str) -> Box<ProgressReporter> {
+        Box::new(SilentProgressReporter {})
+    }
+}
@@ -0,0 +1,204 @@
+use std::str::FromStr;
+
+use crate::api::checking::QualityStatus;
+use crate::commands::check::FileCheckResult;
+
+pub const EXIT_CODE_QUALITY_GATE_FAILED: i32 = 2;
+pub const EXIT_CODE_CHECK_ERRORS: i32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FailOn {
+    /// Fails if a file has the status red.
+    Red,
+    /// Fails if a file has the status yellow or red.
+    Yellow,
+    /// Fails if a file could not be checked.
+    Error,
+}
+
+impl FailOn {
+    pub const VALUES: &'static [&'static str] = &["red", "yellow", "error"];
+}
+
+impl FromStr for FailOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "red" => Ok(FailOn::Red),
+            "yellow" => Ok(FailOn::Yellow),
+            "error" => Ok(FailOn::Error),
+            _ => Err(format!("Unknown fail-on condition \"{}\"", s))
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct QualityGate {
+    pub min_score: Option<f64>,
+    pub fail_on: Vec<FailOn>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum QualityGateResult {
+    Passed,
+    Failed,
+    CheckErrors,
+}
+
+impl QualityGateResult {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            QualityGateResult::Passed => 0,
+            QualityGateResult::Failed => EXIT_CODE_QUALITY_GATE_FAILED,
+            QualityGateResult::CheckErrors => EXIT_CODE_CHECK_ERRORS,
+        }
+    }
+}
+
+impl QualityGate {
+    /// Returns the result of the gate together with a human readable reason per violating file.
+    /// Check errors win over quality violations, because the quality of those files is unknown.
+    pub fn evaluate(&self, results: &[FileCheckResult]) -> (QualityGateResult, Vec<String>) {
+        let mut violations = vec![];
+        let mut check_errors = false;
+
+        for file_result in results {
+            match file_result.quality() {
+                Ok(quality) => {
+                    if let Some(min_score) = self.min_score {
+                        if quality.score < min_score {
+                            violations.push(format!("{}: score {} is below {}", file_result.path, quality.score, min_score));
+                            continue;
+                        }
+                    }
+                    if self.fails_on_status(&quality.status) {
+                        violations.push(format!("{}: status is {:?}", file_result.path, quality.status));
+                    }
+                }
+                Err(error) => {
+                    if self.fail_on.contains(&FailOn::Error) {
+                        check_errors = true;
+                        violations.push(format!("{}: {}", file_result.path, error.title()));
+                    }
+                }
+            }
+        }
+
+        let result = if check_errors {
+            QualityGateResult::CheckErrors
+        } else if !violations.is_empty() {
+            QualityGateResult::Failed
+        } else {
+            QualityGateResult::Passed
+        };
+
+        (result, violations)
+    }
+
+    fn fails_on_status(&self, status: &QualityStatus) -> bool {
+        match status {
+            QualityStatus::red => self.fail_on.contains(&FailOn::Red) || self.fail_on.contains(&FailOn::Yellow),
+            QualityStatus::yellow => self.fail_on.contains(&FailOn::Yellow),
+            QualityStatus::green => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::api::errors::ApiError;
+    use crate::commands::check::{CheckedDocument, DocumentContent};
+    use crate::commands::check::encoding::TextEncoding;
+    use super::*;
+
+    fn checked_file(path: &str, score: f64, status: &str) -> FileCheckResult {
+        let check_result = serde_json::from_value(json!({
+            "quality": {"score": score, "status": status},
+        })).unwrap();
+        FileCheckResult {
+            path: path.to_string(),
+            reference: None,
+            check_options: Arc::default(),
+            result: Ok(CheckedDocument {
+                content: DocumentContent::Text(String::new(), TextEncoding::default()),
+                check_result,
+                partial_check_ranges: None,
+            }),
+        }
+    }
+
+    fn failed_file(path: &str) -> FileCheckResult {
+        FileCheckResult {
+            path: path.to_string(),
+            reference: None,
+            check_options: Arc::default(),
+            result: Err(ApiError::Network("connection refused".to_string())),
+        }
+    }
+
+    fn gate(min_score: Option<f64>, fail_on: &[FailOn]) -> QualityGate {
+        QualityGate { min_score, fail_on: fail_on.to_vec() }
+    }
+
+    #[test]
+    fn passes_without_violations() {
+        let results = [checked_file("a.md", 80.0, "green"), checked_file("b.md", 70.0, "yellow")];
+        let (result, violations) = gate(Some(70.0), &[FailOn::Red, FailOn::Error]).evaluate(&results);
+        assert_eq!(result, QualityGateResult::Passed);
+        assert!(violations.is_empty());
+        assert_eq!(result.exit_code(), 0);
+    }
+
+    #[test]
+    fn fails_below_the_min_score() {
+        let results = [checked_file("a.md", 80.0, "green"), checked_file("b.md", 69.5, "green")];
+        let (result, violations) = gate(Some(70.0), &[]).evaluate(&results);
+        assert_eq!(result, QualityGateResult::Failed);
+        assert_eq!(violations, vec!["b.md: score 69.5 is below 70"]);
+        assert_eq!(result.exit_code(), 2);
+    }
+
+    #[test]
+    fn fail_on_red_ignores_yellow() {
+        let results = [checked_file("a.md", 60.0, "yellow"), checked_file("b.md", 30.0, "red")];
+        let (result, violations) = gate(None, &[FailOn::Red]).evaluate(&results);
+        assert_eq!(result, QualityGateResult::Failed);
+        assert_eq!(violations, vec!["b.md: status is red"]);
+    }
+
+    #[test]
+    fn fail_on_yellow_includes_red() {
+        let results = [checked_file("a.md", 90.0, "green"), checked_file("b.md", 60.0, "yellow"), checked_file("c.md", 30.0, "red")];
+        let (result, violations) = gate(None, &[FailOn::Yellow]).evaluate(&results);
+        assert_eq!(result, QualityGateResult::Failed);
+        assert_eq!(violations, vec!["b.md: status is yellow", "c.md: status is red"]);
+    }
+
+    #[test]
+    fn fail_on_error_reports_files_which_could_not_be_checked() {
+        let results = [checked_file("a.md", 30.0, "red"), failed_file("b.md")];
+        let (result, violations) = gate(Some(70.0), &[FailOn::Error]).evaluate(&results);
+        assert_eq!(result, QualityGateResult::CheckErrors);
+        assert_eq!(violations, vec!["a.md: score 30 is below 70", "b.md: Network error"]);
+        assert_eq!(result.exit_code(), 3);
+    }
+
+    #[test]
+    fn ignores_files_which_could_not_be_checked_without_fail_on_error() {
+        let (result, violations) = gate(Some(70.0), &[FailOn::Red, FailOn::Yellow]).evaluate(&[failed_file("a.md")]);
+        assert_eq!(result, QualityGateResult::Passed);
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn the_readme_documents_the_exit_codes() {
+        let readme = include_str!("../../../README.md");
+        assert!(readme.contains(&format!("| {} | At least one file is below `--min-score`", EXIT_CODE_QUALITY_GATE_FAILED)));
+        assert!(readme.contains(&format!("| {} | At least one file could not be checked", EXIT_CODE_CHECK_ERRORS)));
+    }
+}
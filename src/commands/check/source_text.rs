@@ -0,0 +1,119 @@
+/// A 1-based line and column. The column counts unicode code points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Maps the offsets of the Acrolinx API to byte offsets and positions in the checked text.
+/// The API counts UTF-16 code units (like Java and JavaScript strings do).
+pub struct SourceText<'a> {
+    text: &'a str,
+    byte_offset_by_utf16_offset: Vec<usize>,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceText<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let mut byte_offset_by_utf16_offset = Vec::with_capacity(text.len() + 1);
+        let mut line_starts = vec![0];
+
+        for (byte_offset, c) in text.char_indices() {
+            for _ in 0..c.len_utf16() {
+                byte_offset_by_utf16_offset.push(byte_offset);
+            }
+            if c == '\n' {
+                line_starts.push(byte_offset + 1);
+            }
+        }
+        byte_offset_by_utf16_offset.push(text.len());
+
+        SourceText { text, byte_offset_by_utf16_offset, line_starts }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Offsets behind the end of the text are clamped to the end of the text.
+    pub fn byte_offset(&self, api_offset: usize) -> usize {
+        let last_index = self.byte_offset_by_utf16_offset.len() - 1;
+        self.byte_offset_by_utf16_offset[api_offset.min(last_index)]
+    }
+
+    pub fn position(&self, api_offset: usize) -> Position {
+        self.position_of_byte_offset(self.byte_offset(api_offset))
+    }
+
+    pub fn position_of_byte_offset(&self, byte_offset: usize) -> Position {
+        let line_index = match self.line_starts.binary_search(&byte_offset) {
+            Ok(index) => index,
+            Err(index) => index - 1,
+        };
+        let line_start = self.line_starts[line_index];
+        Position {
+            line: line_index + 1,
+            column: self.text[line_start..byte_offset].chars().count() + 1,
+        }
+    }
+
+    /// Lines behind the end of the text start at the end of the text. `line` is 1-based.
+    pub fn api_offset_of_line(&self, line: usize) -> usize {
+        let start = self.line_starts.get(line - 1).cloned().unwrap_or(self.text.len());
+        self.text[..start].encode_utf16().count()
+    }
+
+    /// Returns the line without the line break. `line` is 1-based.
+    pub fn line(&self, line: usize) -> &'a str {
+        let start = self.line_starts[line - 1];
+        let end = self.line_starts.get(line).cloned().unwrap_or(self.text.len());
+        self.text[start..end].trim_end_matches(&['\n', '\r'][..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_utf16_offsets_to_byte_offsets() {
+        // "ä" has 2 bytes and 1 UTF-16 code unit, "😀" has 4 bytes and 2 UTF-16 code units.
+        let source_text = SourceText::new("aä😀b");
+        assert_eq!(source_text.byte_offset(0), 0);
+        assert_eq!(source_text.byte_offset(1), 1);
+        assert_eq!(source_text.byte_offset(2), 3);
+        assert_eq!(source_text.byte_offset(4), 7);
+        assert_eq!(source_text.byte_offset(5), 8);
+    }
+
+    #[test]
+    fn clamps_offsets_behind_the_end() {
+        assert_eq!(SourceText::new("abc").byte_offset(99), 3);
+        assert_eq!(SourceText::new("").byte_offset(1), 0);
+    }
+
+    #[test]
+    fn positions_count_code_points() {
+        let source_text = SourceText::new("a\nä😀c");
+        assert_eq!(source_text.position(0), Position { line: 1, column: 1 });
+        assert_eq!(source_text.position(2), Position { line: 2, column: 1 });
+        assert_eq!(source_text.position(5), Position { line: 2, column: 3 });
+    }
+
+    #[test]
+    fn lines_have_no_line_breaks() {
+        let source_text = SourceText::new("first\r\nsecond\nlast");
+        assert_eq!(source_text.line(1), "first");
+        assert_eq!(source_text.line(2), "second");
+        assert_eq!(source_text.line(3), "last");
+    }
+
+    #[test]
+    fn api_offsets_of_lines() {
+        let source_text = SourceText::new("a😀\nb\n");
+        assert_eq!(source_text.api_offset_of_line(1), 0);
+        assert_eq!(source_text.api_offset_of_line(2), 4);
+        assert_eq!(source_text.api_offset_of_line(3), 6);
+        assert_eq!(source_text.api_offset_of_line(9), 6);
+    }
+}
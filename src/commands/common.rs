@@ -2,29 +2,43 @@ use clap::crate_version;
 use crate::api::AcroApi;
 use crate::api::AcroApiProps;
 use crate::api::ClientInformation;
+use crate::api::HttpOptions;
 use crate::api::signin::SigninRequestResponse;
 use log::info;
 use crate::api::signin::LoggedInData;
 use crate::utils::open_url;
 use crate::api::signin::AccessToken;
+use crate::commands::errors::CommandError;
+use crate::config::save_access_token;
 
+#[derive(Clone)]
 pub struct CommonCommandConfig {
     pub acrolinx_address: String,
     pub access_token: Option<AccessToken>,
     pub silent: bool,
+    /// Messages go to stderr, because stdout is reserved for one machine readable document.
+    pub machine_readable: bool,
     pub open_url: bool,
+    /// Saves the access token of an interactive sign-in into the config file.
+    pub save_access_token: bool,
+    pub http: HttpOptions,
+    pub locale: String,
+    /// The selected profile of the config file.
+    pub profile: Option<String>,
 }
 
-pub fn connect(config: &CommonCommandConfig) -> AcroApi {
-    AcroApi::new(AcroApiProps {
+pub fn connect(config: &CommonCommandConfig) -> Result<AcroApi, CommandError> {
+    let api = AcroApi::new(AcroApiProps {
         server_url: config.acrolinx_address.clone(),
-        locale: "en".to_string(),
+        locale: config.locale.clone(),
         client: ClientInformation {
             name: "Acrusto".to_string(),
             signature: "dummyClientSignature".to_string(),
             version: crate_version!().to_string(),
         },
-    }, config.access_token.clone())
+        http: config.http.clone(),
+    }, config.access_token.clone())?;
+    Ok(api)
 }
 
 pub struct ConnectAndSigninResult {
@@ -32,35 +46,47 @@ pub struct ConnectAndSigninResult {
     pub signin_details: LoggedInData,
 }
 
-pub fn connect_and_signin(config: &CommonCommandConfig) -> ConnectAndSigninResult {
-    let mut api = connect(&config);
+pub fn connect_and_signin(config: &CommonCommandConfig) -> Result<ConnectAndSigninResult, CommandError> {
+    let mut api = connect(&config)?;
 
     info!("Yeah, there is a server: {:?}", api.server_info());
 
-    let signin_response = api.signin().unwrap();
+    let signin_response = api.signin()?;
     info!("signin_response = {:?}", signin_response);
 
+    let print = |message: &str| if config.machine_readable { eprintln!("{}", message) } else { println!("{}", message) };
+
     let signin_details = match signin_response {
         SigninRequestResponse::SigninLinks(signin_links_response) => {
             if !config.silent {
-                println!("Please signin at");
+                print("Please signin at");
             }
-            println!("{}", signin_links_response.links.interactive);
+            print(&signin_links_response.links.interactive);
 
             if config.open_url {
-                open_url(&signin_links_response.links.interactive).unwrap();
+                if let Err(error) = open_url(&signin_links_response.links.interactive) {
+                    eprintln!("{}", error);
+                }
             }
 
-            let signin_details = api.wait_for_signin(&signin_links_response.links).unwrap();
+            let signin_details = api.wait_for_signin(&signin_links_response.links)?;
 
             if config.silent {
                 // TODO: As JSON?
-                println!("{}", signin_details.data.accessToken);
-            } else {
+                print(&signin_details.data.accessToken);
+            } else if !config.save_access_token {
                 println!("You can use the following token to sign in: ");
                 println!("{}\n", signin_details.data.accessToken);
             }
 
+            if config.save_access_token {
+                let config_file_path = save_access_token(config.profile.as_deref(), &config.acrolinx_address, &signin_details.data.accessToken)
+                    .map_err(|error| CommandError::Io(format!("Can't save the access token because of {}", error)))?;
+                if !config.silent {
+                    println!("Saved the access token to {}\n", config_file_path.display());
+                }
+            }
+
             api.set_access_token(&signin_details.data.accessToken);
             signin_details
         }
@@ -74,5 +100,5 @@ pub fn connect_and_signin(config: &CommonCommandConfig) -> ConnectAndSigninResul
         println!("You're signed in as \"{}\"", signin_details.data.user.username);
     }
 
-    ConnectAndSigninResult { api, signin_details: signin_details.data }
+    Ok(ConnectAndSigninResult { api, signin_details: signin_details.data })
 }
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::io;
+
+use serde_json::json;
+
+use crate::api::errors::ApiError;
+
+/// Exit codes for failed commands follow the conventions of sysexits.h.
+pub const EXIT_CODE_USAGE: i32 = 64;
+pub const EXIT_CODE_UNAVAILABLE: i32 = 69;
+pub const EXIT_CODE_SOFTWARE: i32 = 70;
+pub const EXIT_CODE_IO_ERROR: i32 = 74;
+pub const EXIT_CODE_TEMPORARY_FAILURE: i32 = 75;
+pub const EXIT_CODE_NO_PERMISSION: i32 = 77;
+pub const EXIT_CODE_CONFIG: i32 = 78;
+/// Like shells report a process, which has been stopped by Ctrl-C.
+pub const EXIT_CODE_CANCELLED: i32 = 130;
+
+const HTTP_STATUS_TOO_MANY_REQUESTS: u16 = 429;
+
+#[derive(Debug)]
+pub enum CommandError {
+    Api(ApiError),
+    /// Invalid arguments like malformed file patterns.
+    Usage(String),
+    /// The server or the configuration doesn't provide what the command needs.
+    Config(String),
+    /// Reading or writing local files failed.
+    Io(String),
+}
+
+impl CommandError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandError::Api(api_error) => match api_error {
+                ApiError::AuthExpired(_) => EXIT_CODE_NO_PERMISSION,
+                ApiError::Timeout(_) => EXIT_CODE_TEMPORARY_FAILURE,
+                ApiError::Network(_) => EXIT_CODE_UNAVAILABLE,
+                ApiError::Decode(_) => EXIT_CODE_SOFTWARE,
+                ApiError::Io(_) | ApiError::Encoding(_) => EXIT_CODE_IO_ERROR,
+                ApiError::Unsupported(_) => EXIT_CODE_CONFIG,
+                ApiError::Cancelled => EXIT_CODE_CANCELLED,
+                ApiError::Server(_) => match api_error.status() {
+                    Some(HTTP_STATUS_TOO_MANY_REQUESTS) => EXIT_CODE_TEMPORARY_FAILURE,
+                    Some(status) if status >= 500 => EXIT_CODE_UNAVAILABLE,
+                    _ => EXIT_CODE_SOFTWARE,
+                },
+            },
+            CommandError::Usage(_) => EXIT_CODE_USAGE,
+            CommandError::Config(_) => EXIT_CODE_CONFIG,
+            CommandError::Io(_) => EXIT_CODE_IO_ERROR,
+        }
+    }
+
+    fn error_type(&self) -> &str {
+        match self {
+            CommandError::Api(api_error) => api_error.error_type(),
+            CommandError::Usage(_) => "usage",
+            CommandError::Config(_) => "config",
+            CommandError::Io(_) => "io",
+        }
+    }
+
+    /// A hint how to solve the problem.
+    fn hint(&self) -> Option<&str> {
+        match self {
+            CommandError::Api(ApiError::AuthExpired(_)) =>
+                Some("Please sign in again with \"acrusto signin\" or pass a valid --access-token."),
+            CommandError::Api(ApiError::Network(_)) =>
+                Some("Please check the --acrolinx-address and your network connection."),
+            CommandError::Api(ApiError::Timeout(_)) =>
+                Some("The server might be busy. Try again later or increase --timeout."),
+            _ => None,
+        }
+    }
+
+    /// Prints the error to stderr or as JSON object to stdout, if the output should be machine readable.
+    pub fn print(&self, json: bool) {
+        if json {
+            let error_json = json!({
+                "error": {
+                    "type": self.error_type(),
+                    "message": self.to_string(),
+                    "status": match self { CommandError::Api(api_error) => api_error.status(), _ => None },
+                    "exitCode": self.exit_code(),
+                }
+            });
+            println!("{}", serde_json::to_string_pretty(&error_json).unwrap_or_default());
+        } else {
+            eprintln!("Error: {}", self);
+            if let Some(hint) = self.hint() {
+                eprintln!("{}", hint);
+            }
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::Api(api_error) => write!(f, "{}", api_error),
+            CommandError::Usage(message) | CommandError::Config(message) | CommandError::Io(message) =>
+                write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<ApiError> for CommandError {
+    fn from(api_error: ApiError) -> CommandError {
+        CommandError::Api(api_error)
+    }
+}
+
+impl From<io::Error> for CommandError {
+    fn from(io_error: io::Error) -> CommandError {
+        CommandError::Io(io_error.to_string())
+    }
+}
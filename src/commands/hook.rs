@@ -0,0 +1,62 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::check::git::run_git;
+use crate::commands::check::quality_gate::QualityGate;
+use crate::commands::errors::CommandError;
+
+/// Identifies hooks, which can be replaced without --force.
+static HOOK_MARKER: &str = "Installed by \"acrusto hook install\".";
+
+/// Writes a pre-commit hook, which checks the staged files with `acrusto hook run`.
+/// The hook calls this executable, because git runs hooks with a different PATH in some GUIs and IDEs.
+/// `global_args` are passed to acrusto, like the selected server profile.
+pub fn install_hook(global_args: &[String], force: bool, quality_gate: &QualityGate) -> Result<(), CommandError> {
+    // Respects core.hooksPath.
+    let hook_path = PathBuf::from(run_git(&["rev-parse", "--git-path", "hooks/pre-commit"])?.trim_end());
+
+    if let Ok(existing_hook) = fs::read_to_string(&hook_path) {
+        if !force && !existing_hook.contains(HOOK_MARKER) {
+            return Err(CommandError::Usage(format!(
+                "There is already a pre-commit hook at {}. Use --force to replace it.", hook_path.display())));
+        }
+    }
+
+    let executable = env::current_exe()
+        .map_err(|error| CommandError::Io(format!("Can't find the acrusto executable because of {}", error)))?;
+    let mut command = vec![shell_quote(&executable.to_string_lossy())];
+    command.extend(global_args.iter().map(|arg| shell_quote(arg)));
+    command.push("hook run".to_string());
+    let script = format!("#!/bin/sh\n\
+                          # Checks the staged files with Acrolinx. Skip it with \"git commit --no-verify\".\n\
+                          # {}\n\
+                          exec {}\n", HOOK_MARKER, command.join(" "));
+
+    if let Some(hooks_dir) = hook_path.parent() {
+        fs::create_dir_all(hooks_dir)?;
+    }
+    fs::write(&hook_path, script)?;
+    make_executable(&hook_path)?;
+
+    println!("Installed the pre-commit hook at {}.", hook_path.display());
+    if quality_gate.min_score.is_none() && quality_gate.fail_on.is_empty() {
+        println!("The hook doesn't block commits, because neither min_score nor fail_on is configured.");
+    }
+    Ok(())
+}
+
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
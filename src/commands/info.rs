@@ -1,7 +1,11 @@
+use crate::api::errors::ApiError;
 use crate::commands::common::connect;
 use crate::commands::common::CommonCommandConfig;
+use crate::commands::errors::CommandError;
 
-pub fn server_info(config: &CommonCommandConfig) {
-    let api = connect(&config);
-    println!("{}", serde_json::to_string_pretty(&api.server_info().unwrap()).unwrap());
+pub fn server_info(config: &CommonCommandConfig) -> Result<(), CommandError> {
+    let api = connect(&config)?;
+    let server_info = api.server_info()?;
+    println!("{}", serde_json::to_string_pretty(&server_info).map_err(ApiError::from)?);
+    Ok(())
 }
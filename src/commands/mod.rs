@@ -1,5 +1,7 @@
 pub mod common;
+pub mod errors;
 pub mod info;
 pub mod signin;
 pub mod capabilities;
-pub mod check;
\ No newline at end of file
+pub mod check;
+pub mod hook;
\ No newline at end of file
@@ -1,7 +1,9 @@
 use crate::commands::common::CommonCommandConfig;
 use crate::commands::common::connect_and_signin;
+use crate::commands::errors::CommandError;
 
 
-pub fn signin_command(config: &CommonCommandConfig) {
-    connect_and_signin(&config);
+pub fn signin_command(config: &CommonCommandConfig) -> Result<(), CommandError> {
+    connect_and_signin(&config)?;
+    Ok(())
 }
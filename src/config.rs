@@ -1,9 +1,14 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
 use std;
-use std::fs::File;
-use std::io::Read;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
 use dirs::home_dir;
+use glob::Pattern;
 use serde_derive::Deserialize;
+use serde_json::{Map, Value};
 
 type GenError = Box<std::error::Error>;
 
@@ -12,15 +17,203 @@ type GenError = Box<std::error::Error>;
 pub struct Config {
     pub acrolinx_address: Option<String>,
     pub access_token: Option<String>,
+    /// Saves the access token of each interactive sign-in, like `signin --save`.
+    #[serde(default)]
+    pub save_access_token: bool,
+    pub guidance_profile: Option<String>,
+    pub locale: Option<String>,
+    pub max_concurrent: Option<usize>,
+    /// Named settings for different servers like "staging" and "production".
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ServerProfile>,
+    /// The profile, which is used if no `--profile` is given.
+    pub default_profile: Option<String>,
+    /// File patterns, which are checked if no `--files` are given.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Patterns of files in directories, which are checked instead of files with default extensions.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Patterns of files, which are never checked.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub min_score: Option<f64>,
+    #[serde(default)]
+    pub fail_on: Vec<String>,
+    /// Guidance profiles for files, which match a pattern. The first match wins.
+    #[serde(default)]
+    pub path_guidance_profiles: Vec<PathGuidanceProfile>,
+    /// Content formats for files, which match a pattern. The first match wins.
+    #[serde(default)]
+    pub path_content_formats: Vec<PathContentFormat>,
+    /// Encodings of text files without byte order mark, which match a pattern. The first match wins.
+    #[serde(default)]
+    pub path_encodings: Vec<PathEncoding>,
+    /// The guidance profile of the project config, which wins over the guidance profile of a server profile.
+    #[serde(skip)]
+    pub project_guidance_profile: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PathGuidanceProfile {
+    pub pattern: String,
+    pub guidance_profile: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PathContentFormat {
+    pub pattern: String,
+    pub content_format: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PathEncoding {
+    pub pattern: String,
+    pub encoding: String,
+}
+
+/// Overrides the top level settings of the config, if selected.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct ServerProfile {
+    pub acrolinx_address: Option<String>,
+    pub access_token: Option<String>,
+    pub guidance_profile: Option<String>,
+    pub locale: Option<String>,
+    pub max_concurrent: Option<usize>,
 }
 
 impl Config {
-    pub fn read() -> Self {
-        home_dir().and_then(|mut path| {
+    pub fn path() -> Option<PathBuf> {
+        home_dir().map(|mut path| {
             path.push(".config");
             path.push("acrusto.json");
-            Self::read_from_path(&path).ok()
-        }).unwrap_or_default()
+            path
+        })
+    }
+
+    /// Reads the user config and merges the nearest project config over it.
+    pub fn read() -> Self {
+        let user_config = Self::path().map(|path| read_or_warn(&path)).unwrap_or_default();
+
+        let current_dir = match env::current_dir() {
+            Ok(current_dir) => current_dir,
+            Err(_) => return user_config,
+        };
+        match find_project_config(&current_dir) {
+            Some(project_config_path) => {
+                let mut project_config = read_or_warn(&project_config_path).project_settings(&project_config_path);
+                // The patterns of the project config are relative to its directory.
+                let project_dir = project_config_path.parent().unwrap_or(&current_dir);
+                // The files are searched from the current directory.
+                let depth = current_dir.components().count() - project_dir.components().count();
+                project_config.files = project_config.files.iter().map(|pattern| rebase_pattern(pattern, depth)).collect();
+                // The other patterns are matched against the absolute paths of the files.
+                project_config.include = anchor_patterns(&project_config.include, project_dir);
+                project_config.exclude = anchor_patterns(&project_config.exclude, project_dir);
+                for path_guidance_profile in &mut project_config.path_guidance_profiles {
+                    path_guidance_profile.pattern = anchor_pattern(&path_guidance_profile.pattern, project_dir);
+                }
+                for path_content_format in &mut project_config.path_content_formats {
+                    path_content_format.pattern = anchor_pattern(&path_content_format.pattern, project_dir);
+                }
+                for path_encoding in &mut project_config.path_encodings {
+                    path_encoding.pattern = anchor_pattern(&path_encoding.pattern, project_dir);
+                }
+                user_config.merge(project_config)
+            }
+            None => user_config
+        }
+    }
+
+    /// A repository must not choose the server, which gets the access token of the user.
+    /// So project configs can only set how files are checked.
+    fn project_settings(self, config_file_path: &Path) -> Config {
+        let ignored_settings: Vec<&str> = vec![
+            ("acrolinx_address", self.acrolinx_address.is_some()),
+            ("access_token", self.access_token.is_some()),
+            ("save_access_token", self.save_access_token),
+            ("locale", self.locale.is_some()),
+            ("max_concurrent", self.max_concurrent.is_some()),
+            ("profiles", !self.profiles.is_empty()),
+            ("default_profile", self.default_profile.is_some()),
+        ].into_iter().filter(|(_, is_set)| *is_set).map(|(name, _)| name).collect();
+        if !ignored_settings.is_empty() {
+            eprintln!("Ignoring {} of the project config {}, because only the user config can set them.",
+                      ignored_settings.join(", "), config_file_path.display());
+        }
+
+        Config {
+            project_guidance_profile: self.guidance_profile,
+            files: self.files,
+            include: self.include,
+            exclude: self.exclude,
+            min_score: self.min_score,
+            fail_on: self.fail_on,
+            path_guidance_profiles: self.path_guidance_profiles,
+            path_content_formats: self.path_content_formats,
+            path_encodings: self.path_encodings,
+            ..Config::default()
+        }
+    }
+
+    /// Settings of the other config override the settings of this one.
+    fn merge(mut self, other: Config) -> Config {
+        self.profiles.extend(other.profiles);
+        let mut path_guidance_profiles = other.path_guidance_profiles;
+        path_guidance_profiles.extend(self.path_guidance_profiles);
+        let mut path_content_formats = other.path_content_formats;
+        path_content_formats.extend(self.path_content_formats);
+        let mut path_encodings = other.path_encodings;
+        path_encodings.extend(self.path_encodings);
+        Config {
+            acrolinx_address: other.acrolinx_address.or(self.acrolinx_address),
+            access_token: other.access_token.or(self.access_token),
+            save_access_token: other.save_access_token || self.save_access_token,
+            guidance_profile: other.guidance_profile.or(self.guidance_profile),
+            locale: other.locale.or(self.locale),
+            max_concurrent: other.max_concurrent.or(self.max_concurrent),
+            profiles: self.profiles,
+            default_profile: other.default_profile.or(self.default_profile),
+            files: if other.files.is_empty() { self.files } else { other.files },
+            include: if other.include.is_empty() { self.include } else { other.include },
+            exclude: if other.exclude.is_empty() { self.exclude } else { other.exclude },
+            min_score: other.min_score.or(self.min_score),
+            fail_on: if other.fail_on.is_empty() { self.fail_on } else { other.fail_on },
+            path_guidance_profiles,
+            path_content_formats,
+            path_encodings,
+            project_guidance_profile: other.project_guidance_profile.or(self.project_guidance_profile),
+        }
+    }
+
+    /// Merges the selected profile (or the default profile) over the top level settings.
+    /// The guidance profile of the project config wins over both.
+    pub fn server_profile(&self, profile_name: Option<&str>) -> Result<ServerProfile, String> {
+        let top_level = ServerProfile {
+            acrolinx_address: self.acrolinx_address.clone(),
+            access_token: self.access_token.clone(),
+            guidance_profile: self.project_guidance_profile.clone().or_else(|| self.guidance_profile.clone()),
+            locale: self.locale.clone(),
+            max_concurrent: self.max_concurrent,
+        };
+
+        let profile_name = match profile_name.or(self.default_profile.as_deref()) {
+            Some(profile_name) => profile_name,
+            None => return validate_server_profile(top_level),
+        };
+        let profile = self.profiles.get(profile_name).ok_or_else(|| {
+            let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            format!("Unknown profile \"{}\". Available profiles: {}", profile_name,
+                    if names.is_empty() { "none".to_string() } else { names.join(", ") })
+        })?.clone();
+
+        validate_server_profile(ServerProfile {
+            acrolinx_address: profile.acrolinx_address.or(top_level.acrolinx_address),
+            access_token: profile.access_token.or(top_level.access_token),
+            guidance_profile: self.project_guidance_profile.clone().or(profile.guidance_profile).or(top_level.guidance_profile),
+            locale: profile.locale.or(top_level.locale),
+            max_concurrent: profile.max_concurrent.or(top_level.max_concurrent),
+        })
     }
 
     pub fn read_from_path(config_file_path: &Path) -> Result<Self, GenError> {
@@ -32,8 +225,185 @@ impl Config {
         let mut file_content = String::new();
         file.read_to_string(&mut file_content)?;
 
-        let config: Self = serde_json::from_str(&file_content)?;
+        let config: Self = if config_file_path.extension().is_some_and(|extension| extension == "toml") {
+            toml::from_str(&file_content)?
+        } else {
+            serde_json::from_str(&file_content)?
+        };
 
         Ok(config)
     }
-}
\ No newline at end of file
+}
+
+fn validate_server_profile(server_profile: ServerProfile) -> Result<ServerProfile, String> {
+    if server_profile.max_concurrent == Some(0) {
+        return Err("max_concurrent must be at least 1".to_string());
+    }
+    Ok(server_profile)
+}
+
+static PROJECT_CONFIG_FILE_NAMES: &[&str] = &[".acrusto.json", "acrusto.toml"];
+
+/// Looks for a project config in the directory and its parents, like git looks for its repository.
+pub fn find_project_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .flat_map(|ancestor| PROJECT_CONFIG_FILE_NAMES.iter().map(move |file_name| ancestor.join(file_name)))
+        .find(|path| path.is_file())
+}
+
+/// A broken config file should not prevent commands, which get their settings from arguments.
+fn read_or_warn(config_file_path: &Path) -> Config {
+    Config::read_from_path(config_file_path).unwrap_or_else(|error| {
+        eprintln!("Ignoring the config file {} because of {}", config_file_path.display(), error);
+        Config::default()
+    })
+}
+
+fn anchor_patterns(patterns: &[String], dir: &Path) -> Vec<String> {
+    patterns.iter().map(|pattern| anchor_pattern(pattern, dir)).collect()
+}
+
+/// Makes a pattern, which is relative to the directory, absolute.
+fn anchor_pattern(pattern: &str, dir: &Path) -> String {
+    if Path::new(pattern).is_absolute() {
+        pattern.to_string()
+    } else {
+        format!("{}/{}", Pattern::escape(&dir.to_string_lossy()), pattern.trim_start_matches("./"))
+    }
+}
+
+/// Makes a relative pattern of a directory `depth` levels above the current directory relative to the current directory.
+fn rebase_pattern(pattern: &str, depth: usize) -> String {
+    if depth == 0 || Path::new(pattern).is_absolute() {
+        pattern.to_string()
+    } else {
+        "../".repeat(depth) + pattern.trim_start_matches("./")
+    }
+}
+
+/// Writes the server address and the access token into the config file (or into the given profile)
+/// and keeps its other settings. Only the owner may read the file, because the access token is a secret.
+pub fn save_access_token(profile_name: Option<&str>, acrolinx_address: &str, access_token: &str) -> Result<PathBuf, GenError> {
+    let config_file_path = Config::path().ok_or("Can't find the home directory")?;
+
+    let mut config_json = if config_file_path.exists() {
+        match serde_json::from_str(&fs::read_to_string(&config_file_path)?)? {
+            Value::Object(map) => map,
+            _ => return Err(format!("{} contains no JSON object", config_file_path.display()).into()),
+        }
+    } else {
+        Map::new()
+    };
+
+    let settings = match profile_name {
+        Some(profile_name) => {
+            let profiles = config_json.entry("profiles").or_insert_with(|| Value::Object(Map::new()));
+            let profile = profiles.as_object_mut().ok_or("\"profiles\" is no JSON object")?
+                .entry(profile_name).or_insert_with(|| Value::Object(Map::new()));
+            profile.as_object_mut().ok_or_else(|| format!("The profile \"{}\" is no JSON object", profile_name))?
+        }
+        None => &mut config_json,
+    };
+    settings.insert("acrolinx_address".to_string(), Value::from(acrolinx_address));
+    settings.insert("access_token".to_string(), Value::from(access_token));
+
+    if let Some(config_dir) = config_file_path.parent() {
+        fs::create_dir_all(config_dir)?;
+    }
+    let mut file = open_private_file(&config_file_path)?;
+    file.write_all(serde_json::to_string_pretty(&config_json)?.as_bytes())?;
+
+    Ok(config_file_path)
+}
+
+#[cfg(unix)]
+fn open_private_file(path: &Path) -> std::io::Result<File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let file = OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    // The mode is only used for new files.
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn open_private_file(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().write(true).create(true).truncate(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebases_relative_patterns_to_the_current_directory() {
+        assert_eq!(rebase_pattern("docs/**/*.md", 0), "docs/**/*.md");
+        assert_eq!(rebase_pattern("./docs/*.md", 2), "../../docs/*.md");
+        assert_eq!(rebase_pattern("/abs/*.md", 2), "/abs/*.md");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn anchored_patterns_match_absolute_paths() {
+        let dir = Path::new("/repo");
+        let exclude = Pattern::new(&anchor_pattern("docs/generated/**", dir)).unwrap();
+        assert!(exclude.matches("/repo/docs/generated/a.md"));
+        assert!(!exclude.matches("/other/docs/generated/a.md"));
+
+        let anywhere = Pattern::new(&anchor_pattern("**/api.md", dir)).unwrap();
+        assert!(anywhere.matches("/repo/api.md"));
+        assert!(anywhere.matches("/repo/docs/v1/api.md"));
+
+        assert_eq!(anchor_pattern("./x/*.md", dir), "/repo/x/*.md");
+        assert_eq!(anchor_pattern("/abs/*.md", dir), "/abs/*.md");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn escapes_the_directory_of_anchored_patterns() {
+        let pattern = Pattern::new(&anchor_pattern("*.md", Path::new("/repo[1]"))).unwrap();
+        assert!(pattern.matches("/repo[1]/a.md"));
+    }
+
+    #[test]
+    fn project_configs_cannot_set_the_server() {
+        let config: Config = serde_json::from_str(r#"{
+            "acrolinx_address": "https://other.example",
+            "access_token": "token",
+            "profiles": {"prod": {"acrolinx_address": "https://other.example"}},
+            "default_profile": "prod",
+            "exclude": ["generated/**"],
+            "min_score": 70
+        }"#).unwrap();
+        let project_config = config.project_settings(Path::new(".acrusto.json"));
+        assert_eq!(project_config.acrolinx_address, None);
+        assert_eq!(project_config.access_token, None);
+        assert!(project_config.profiles.is_empty());
+        assert_eq!(project_config.default_profile, None);
+        assert_eq!(project_config.exclude, vec!["generated/**"]);
+        assert_eq!(project_config.min_score, Some(70.0));
+    }
+
+    #[test]
+    fn the_guidance_profile_of_the_project_config_wins_over_server_profiles() {
+        let user_config: Config = serde_json::from_str(r#"{
+            "guidance_profile": "user",
+            "profiles": {"prod": {"guidance_profile": "prod"}, "staging": {}}
+        }"#).unwrap();
+        let project_config: Config = serde_json::from_str(r#"{"guidance_profile": "project"}"#).unwrap();
+        let config = user_config.merge(project_config.project_settings(Path::new(".acrusto.json")));
+        for profile_name in &[None, Some("prod"), Some("staging")] {
+            assert_eq!(config.server_profile(*profile_name).unwrap().guidance_profile, Some("project".to_string()));
+        }
+    }
+
+    #[test]
+    fn server_profiles_override_the_guidance_profile_of_the_user_config() {
+        let config: Config = serde_json::from_str(r#"{
+            "guidance_profile": "user",
+            "profiles": {"prod": {"guidance_profile": "prod"}, "staging": {}}
+        }"#).unwrap();
+        assert_eq!(config.server_profile(None).unwrap().guidance_profile, Some("user".to_string()));
+        assert_eq!(config.server_profile(Some("prod")).unwrap().guidance_profile, Some("prod".to_string()));
+        assert_eq!(config.server_profile(Some("staging")).unwrap().guidance_profile, Some("user".to_string()));
+    }
+}
@@ -1,6 +1,6 @@
 use std::env;
 
-use clap::{App, Arg, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
 use clap::crate_version;
 use lazy_static::lazy_static;
 use log::Level;
@@ -11,9 +11,22 @@ use crate::commands::capabilities::show_capabilities;
 use crate::commands::check::check;
 use crate::commands::info::server_info;
 use crate::commands::signin::signin_command;
+use crate::commands::hook::install_hook;
 use crate::config::Config;
 use crate::commands::common::CommonCommandConfig;
+use crate::commands::errors::CommandError;
+use crate::commands::errors::{EXIT_CODE_UNAVAILABLE, EXIT_CODE_NO_PERMISSION, EXIT_CODE_CANCELLED};
 use crate::commands::check::CheckCommandOpts;
+use crate::commands::check::output::OutputFormat;
+use crate::commands::check::fix::FixMode;
+use crate::commands::check::git::GitChanges;
+use crate::commands::check::quality_gate::{QualityGate, FailOn};
+use crate::commands::check::quality_gate::{EXIT_CODE_QUALITY_GATE_FAILED, EXIT_CODE_CHECK_ERRORS};
+use std::process;
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::api::HttpOptions;
+use crate::api::retry::RetryPolicy;
 
 mod config;
 mod api;
@@ -28,11 +41,35 @@ static ACCESS_TOKEN_ARG: &str = "access-token";
 static SILENT_FLAG: &str = "silent";
 static LOG_FLAG: &str = "log";
 static OPEN_URL_FLAG: &str = "open";
+static CONNECT_TIMEOUT_ARG: &str = "connect-timeout";
+static TIMEOUT_ARG: &str = "timeout";
+static MAX_RETRIES_ARG: &str = "max-retries";
+static PROFILE_ARG: &str = "profile";
+static LOCALE_ARG: &str = "locale";
 
 static GUIDANCE_PROFILE_ARG: &str = "guidance-profile";
 static FILES_ARG: &str = "files";
 static MAX_CONCURRENT_ARG: &str = "max-concurrent";
 static AUTH_LINKS_FLAG: &str = "auth-links";
+static OUTPUT_FORMAT_ARG: &str = "output-format";
+static MIN_SCORE_ARG: &str = "min-score";
+static FAIL_ON_ARG: &str = "fail-on";
+static CONTENT_FORMAT_ARG: &str = "content-format";
+static ENCODING_ARG: &str = "encoding";
+static INCLUDE_ARG: &str = "include";
+static EXCLUDE_ARG: &str = "exclude";
+static NO_IGNORE_FLAG: &str = "no-ignore";
+static CHANGED_SINCE_ARG: &str = "changed-since";
+static STAGED_FLAG: &str = "staged";
+static JUNIT_REPORT_ARG: &str = "junit-report";
+static SARIF_REPORT_ARG: &str = "sarif-report";
+static SHOW_ISSUES_FLAG: &str = "show-issues";
+static FIX_FLAG: &str = "fix";
+static FIX_PATCH_ARG: &str = "fix-patch";
+static INTERACTIVE_FLAG: &str = "interactive";
+
+static SAVE_FLAG: &str = "save";
+static FORCE_FLAG: &str = "force";
 
 lazy_static! {
     static ref SERVER_ADDRESS_ENV_VAR: String = arg_name_to_env_var(SERVER_ADDRESS_ARG);
@@ -40,28 +77,80 @@ lazy_static! {
     static ref SILENT_ENV_VAR: String = arg_name_to_env_var(SILENT_FLAG);
     static ref LOG_ENV_VAR: String = arg_name_to_env_var(LOG_FLAG);
     static ref OPEN_URL_ENV_VAR: String = arg_name_to_env_var(OPEN_URL_FLAG);
+    static ref CONNECT_TIMEOUT_ENV_VAR: String = arg_name_to_env_var(CONNECT_TIMEOUT_ARG);
+    static ref TIMEOUT_ENV_VAR: String = arg_name_to_env_var(TIMEOUT_ARG);
+    static ref MAX_RETRIES_ENV_VAR: String = arg_name_to_env_var(MAX_RETRIES_ARG);
+    static ref PROFILE_ENV_VAR: String = arg_name_to_env_var(PROFILE_ARG);
+    static ref LOCALE_ENV_VAR: String = arg_name_to_env_var(LOCALE_ARG);
 
     static ref GUIDANCE_PROFILE_ENV_VAR: String = arg_name_to_env_var(GUIDANCE_PROFILE_ARG);
     static ref FILES_ARG_ENV_VAR: String = arg_name_to_env_var(FILES_ARG);
     static ref MAX_CONCURRENT_ENV_VAR: String = arg_name_to_env_var(MAX_CONCURRENT_ARG);
     static ref AUTH_LINKS_ENV_VAR: String = arg_name_to_env_var(AUTH_LINKS_FLAG);
+    static ref OUTPUT_FORMAT_ENV_VAR: String = arg_name_to_env_var(OUTPUT_FORMAT_ARG);
+    static ref MIN_SCORE_ENV_VAR: String = arg_name_to_env_var(MIN_SCORE_ARG);
+    static ref FAIL_ON_ENV_VAR: String = arg_name_to_env_var(FAIL_ON_ARG);
+    static ref CONTENT_FORMAT_ENV_VAR: String = arg_name_to_env_var(CONTENT_FORMAT_ARG);
+    static ref ENCODING_ENV_VAR: String = arg_name_to_env_var(ENCODING_ARG);
+    static ref INCLUDE_ENV_VAR: String = arg_name_to_env_var(INCLUDE_ARG);
+    static ref EXCLUDE_ENV_VAR: String = arg_name_to_env_var(EXCLUDE_ARG);
+    static ref NO_IGNORE_ENV_VAR: String = arg_name_to_env_var(NO_IGNORE_FLAG);
+    static ref CHANGED_SINCE_ENV_VAR: String = arg_name_to_env_var(CHANGED_SINCE_ARG);
+    static ref STAGED_ENV_VAR: String = arg_name_to_env_var(STAGED_FLAG);
+    static ref JUNIT_REPORT_ENV_VAR: String = arg_name_to_env_var(JUNIT_REPORT_ARG);
+    static ref SARIF_REPORT_ENV_VAR: String = arg_name_to_env_var(SARIF_REPORT_ARG);
+    static ref SHOW_ISSUES_ENV_VAR: String = arg_name_to_env_var(SHOW_ISSUES_FLAG);
+    static ref FIX_ENV_VAR: String = arg_name_to_env_var(FIX_FLAG);
+    static ref FIX_PATCH_ENV_VAR: String = arg_name_to_env_var(FIX_PATCH_ARG);
+    static ref INTERACTIVE_ENV_VAR: String = arg_name_to_env_var(INTERACTIVE_FLAG);
+
+    static ref SAVE_ENV_VAR: String = arg_name_to_env_var(SAVE_FLAG);
+    static ref FORCE_ENV_VAR: String = arg_name_to_env_var(FORCE_FLAG);
+
+    static ref CHECK_EXIT_CODES_HELP: String = format!("EXIT CODES:
+    0    All files passed the quality gate.
+    {}    At least one file is below --min-score or has a status named in --fail-on (yellow includes red).
+    {}    At least one file could not be checked and --fail-on contains \"error\".
+    {}   The server can't be reached or is unavailable.
+    {}   The access token is invalid or has expired.
+    {}  The check has been cancelled.",
+        EXIT_CODE_QUALITY_GATE_FAILED, EXIT_CODE_CHECK_ERRORS,
+        EXIT_CODE_UNAVAILABLE, EXIT_CODE_NO_PERMISSION, EXIT_CODE_CANCELLED);
 }
 
 static SUB_COMMAND_SIGN_IN: &str = "signin";
 static SUB_COMMAND_INFO: &str = "info";
 static SUB_COMMAND_CAPABILITIES: &str = "capabilities";
 static SUB_COMMAND_CHECK: &str = "check";
+static SUB_COMMAND_HOOK: &str = "hook";
+static SUB_COMMAND_HOOK_INSTALL: &str = "install";
+static SUB_COMMAND_HOOK_RUN: &str = "run";
 
 fn main() {
     let default_config = Config::read();
+    let profile_name = selected_profile_name().or_else(|| default_config.default_profile.clone());
+    let server_profile = default_config.server_profile(profile_name.as_deref()).unwrap_or_else(|message| {
+        let error = CommandError::Config(message);
+        error.print(false);
+        process::exit(error.exit_code());
+    });
+    let default_max_concurrent = Some(server_profile.max_concurrent.unwrap_or(1).to_string());
+    let default_min_score = default_config.min_score.map(|min_score| min_score.to_string());
+
+    let profile_arg = create_arg(PROFILE_ARG, &PROFILE_ENV_VAR, &None)
+        .short("p")
+        .help("Selects a server profile of the config file. Arguments and environment variables override its settings.");
 
-    let auth_token_arg = create_arg(ACCESS_TOKEN_ARG, &ACCESS_TOKEN_ENV_VAR, &default_config.access_token)
+    let locale_arg = create_arg(LOCALE_ARG, &LOCALE_ENV_VAR, &server_profile.locale)
+        .help("Sets the language of messages and guidance of the Acrolinx Platform (default: en).");
+
+    let auth_token_arg = create_arg(ACCESS_TOKEN_ARG, &ACCESS_TOKEN_ENV_VAR, &server_profile.access_token)
         .short("t")
         .hide_env_values(true)
         .hide_default_value(true)
         .help("Sets an access token to authenticate a user. We recommend setting the access token as an environment variable.");
 
-    let server_address_arg = create_arg(SERVER_ADDRESS_ARG, &SERVER_ADDRESS_ENV_VAR, &default_config.acrolinx_address)
+    let server_address_arg = create_arg(SERVER_ADDRESS_ARG, &SERVER_ADDRESS_ENV_VAR, &server_profile.acrolinx_address)
         .short("a")
         .required(true)
         .help("Sets the URL of the Acrolinx Platform.");
@@ -80,38 +169,140 @@ fn main() {
         .help("Opens interactive sites, like the Dashboard, Sign-in page, and Scorecard.")
         .takes_value(false);
 
-    let guidance_profile_arg = create_arg(GUIDANCE_PROFILE_ARG, &GUIDANCE_PROFILE_ENV_VAR, &None)
+    let connect_timeout_arg = create_arg(CONNECT_TIMEOUT_ARG, &CONNECT_TIMEOUT_ENV_VAR, &None)
+        .validator(validate_seconds)
+        .help("Sets the maximum number of seconds to connect to the Acrolinx Platform (default: 10).");
+
+    let timeout_arg = create_arg(TIMEOUT_ARG, &TIMEOUT_ENV_VAR, &None)
+        .validator(validate_seconds)
+        .help("Sets the maximum number of seconds for each request to the Acrolinx Platform (default: 60).");
+
+    let max_retries_arg = create_arg(MAX_RETRIES_ARG, &MAX_RETRIES_ENV_VAR, &None)
+        .validator(|value| value.parse::<u32>().map(|_| ()).map_err(|e| e.to_string()))
+        .help("Sets how often failed requests are retried with increasing delays (default: 2).");
+
+    let guidance_profile_arg = create_arg(GUIDANCE_PROFILE_ARG, &GUIDANCE_PROFILE_ENV_VAR, &server_profile.guidance_profile)
         .short("i") // TODO: Why i?
-        .help("Sets the guidance profile. See capabilities for available options.");
+        .help("Sets the guidance profile by id, name or language. See capabilities for available options.");
 
-    let max_concurrent_arg = create_arg(MAX_CONCURRENT_ARG, &MAX_CONCURRENT_ENV_VAR, &None)
-        .default_value("1")
+    let max_concurrent_arg = create_arg(MAX_CONCURRENT_ARG, &MAX_CONCURRENT_ENV_VAR, &default_max_concurrent)
+        .validator(validate_max_concurrent)
         .help("Maximum number of concurrent checks.");
 
-    let files_arg = create_arg(FILES_ARG, &FILES_ARG_ENV_VAR, &None)
+    let content_format_arg = create_arg(CONTENT_FORMAT_ARG, &CONTENT_FORMAT_ENV_VAR, &None)
+        .help("Sets the content format like MARKDOWN or XML instead of detecting it by the file extension.");
+
+    let encoding_arg = create_arg(ENCODING_ARG, &ENCODING_ENV_VAR, &None)
+        .help("Sets the encoding of text files without byte order mark like \"latin1\" (default: UTF-8).");
+
+    let mut files_arg = create_arg(FILES_ARG, &FILES_ARG_ENV_VAR, &None)
         .short("f")
         .multiple(true)
-        .required(true)
-        .help(" Sets the relative or absolute path to the file(s) to be checked.");
+        .help(" Sets the relative or absolute path to the file(s) or directories to be checked.");
+    if default_config.files.is_empty() {
+        // Git selects the files in the current directory.
+        files_arg = files_arg.required_unless_one(&[CHANGED_SINCE_ARG, STAGED_FLAG]);
+    }
+
+    let include_arg = create_arg(INCLUDE_ARG, &INCLUDE_ENV_VAR, &None)
+        .multiple(true)
+        .number_of_values(1)
+        .help("Checks the files in directories, which match this pattern, instead of files with known extensions (txt, md, xml, html, dita...).");
+
+    let exclude_arg = create_arg(EXCLUDE_ARG, &EXCLUDE_ENV_VAR, &None)
+        .multiple(true)
+        .number_of_values(1)
+        .help("Skips files and directories, which match this pattern, like \"**/generated/**\".");
+
+    let no_ignore_flag = create_arg(NO_IGNORE_FLAG, &NO_IGNORE_ENV_VAR, &None)
+        .help("Checks files in directories, even if .gitignore or .acrustoignore files ignore them.")
+        .takes_value(false);
+
+    let changed_since_arg = create_arg(CHANGED_SINCE_ARG, &CHANGED_SINCE_ENV_VAR, &None)
+        .help("Checks only the files, which have been changed or added since this git commit, branch or tag.");
+
+    let staged_flag = create_arg(STAGED_FLAG, &STAGED_ENV_VAR, &None)
+        .conflicts_with(CHANGED_SINCE_ARG)
+        .help("Checks only the files, which have been changed or added in the git index.")
+        .takes_value(false);
 
     let auth_links_flag = create_arg(AUTH_LINKS_FLAG, &AUTH_LINKS_ENV_VAR, &None)
         .help("Sets authenticated links in the result files and console output.")
         .takes_value(false);
 
+    let output_format_arg = create_arg(OUTPUT_FORMAT_ARG, &OUTPUT_FORMAT_ENV_VAR, &None)
+        .possible_values(OutputFormat::VALUES)
+        .default_value("text")
+        .help("Sets the format of the check results. \"json\" prints one JSON document for scripting.");
+
+    let min_score_arg = create_arg(MIN_SCORE_ARG, &MIN_SCORE_ENV_VAR, &default_min_score)
+        .validator(|value| value.parse::<f64>().map(|_| ()).map_err(|e| e.to_string()))
+        .help("Fails (exit code 2) if the score of a file is below this value.");
+
+    let fail_on_arg = create_arg(FAIL_ON_ARG, &FAIL_ON_ENV_VAR, &None)
+        .possible_values(FailOn::VALUES)
+        .multiple(true)
+        .use_delimiter(true)
+        .help("Fails if a file has the status \"red\", \"yellow\" (or red) or could not be checked (\"error\").");
+
+    let junit_report_arg = create_arg(JUNIT_REPORT_ARG, &JUNIT_REPORT_ENV_VAR, &None)
+        .help("Writes the check results as JUnit XML file, which CI servers can show as test results.");
+
+    let sarif_report_arg = create_arg(SARIF_REPORT_ARG, &SARIF_REPORT_ENV_VAR, &None)
+        .help("Writes the issues as SARIF 2.1 file, which can be uploaded to code scanning tools.");
+
+    let show_issues_flag = create_arg(SHOW_ISSUES_FLAG, &SHOW_ISSUES_ENV_VAR, &None)
+        .help("Shows the issues of each file with the affected text and suggestions.")
+        .takes_value(false);
+
+    let fix_flag = create_arg(FIX_FLAG, &FIX_ENV_VAR, &None)
+        .help("Applies the first suggestion of each issue to the checked files and rechecks them.")
+        .takes_value(false);
+
+    let fix_patch_arg = create_arg(FIX_PATCH_ARG, &FIX_PATCH_ENV_VAR, &None)
+        .conflicts_with(FIX_FLAG)
+        .help("Writes the fixes of --fix as patch file instead of changing the checked files.");
+
+    let interactive_flag = create_arg(INTERACTIVE_FLAG, &INTERACTIVE_ENV_VAR, &None)
+        .conflicts_with_all(&[FIX_FLAG, FIX_PATCH_ARG])
+        .help("Reviews the issues one by one and writes the accepted changes into the checked files.")
+        .takes_value(false);
+
+    let save_flag = create_arg(SAVE_FLAG, &SAVE_ENV_VAR, &None)
+        .help("Saves the access token and the server address in ~/.config/acrusto.json.")
+        .takes_value(false);
+
+    let force_flag = create_arg(FORCE_FLAG, &FORCE_ENV_VAR, &None)
+        .help("Replaces an existing pre-commit hook.")
+        .takes_value(false);
+
     let mut command_line_parser = App::new("acrusto")
         .version(crate_version!())
         .author("Marco Stahl <shybyte@gmail.com>")
         .about("Unofficial commandline tool for the Acrolinx Platform API")
-        .args(&[server_address_arg, auth_token_arg, silent_flag, log_flag, open_url_flag])
+        .args(&[server_address_arg, auth_token_arg, silent_flag, log_flag, open_url_flag,
+            connect_timeout_arg, timeout_arg, max_retries_arg, profile_arg, locale_arg])
         .subcommand(SubCommand::with_name(SUB_COMMAND_SIGN_IN)
-            .about("Signs in to Acrolinx via the Sign-in page and gets an access token."))
+            .about("Signs in to Acrolinx via the Sign-in page and gets an access token.")
+            .arg(save_flag))
         .subcommand(SubCommand::with_name(SUB_COMMAND_INFO)
             .about("Shows the Acrolinx Platform version and information."))
         .subcommand(SubCommand::with_name(SUB_COMMAND_CAPABILITIES)
             .about("Lists the available check settings."))
         .subcommand(SubCommand::with_name(SUB_COMMAND_CHECK)
             .about("Checks the given file(s) with Acrolinx.")
-            .args(&[guidance_profile_arg, max_concurrent_arg, auth_links_flag, files_arg])
+            .after_help(CHECK_EXIT_CODES_HELP.as_str())
+            .args(&[guidance_profile_arg, content_format_arg, encoding_arg, max_concurrent_arg, auth_links_flag, output_format_arg,
+                min_score_arg, fail_on_arg, junit_report_arg, sarif_report_arg, show_issues_flag, fix_flag, fix_patch_arg, interactive_flag, include_arg, exclude_arg, no_ignore_flag, changed_since_arg, staged_flag, files_arg])
+        )
+        .subcommand(SubCommand::with_name(SUB_COMMAND_HOOK)
+            .about("Checks the staged files before each git commit.")
+            .subcommand(SubCommand::with_name(SUB_COMMAND_HOOK_INSTALL)
+                .about("Installs a git pre-commit hook, which runs \"acrusto hook run\".")
+                .arg(force_flag))
+            .subcommand(SubCommand::with_name(SUB_COMMAND_HOOK_RUN)
+                .about("Checks the staged content of the changed files with the settings of the config files.")
+                .after_help(CHECK_EXIT_CODES_HELP.as_str()))
         );
 
     let args: Vec<_> = env::args().collect();
@@ -126,7 +317,13 @@ fn main() {
         acrolinx_address: matches.value_of(SERVER_ADDRESS_ARG).unwrap().to_string(),
         access_token: access_token_option.map(String::from),
         silent: matches.is_present(SILENT_FLAG),
+        machine_readable: false,
         open_url: matches.is_present(OPEN_URL_FLAG),
+        save_access_token: default_config.save_access_token ||
+            matches.subcommand_matches(SUB_COMMAND_SIGN_IN).is_some_and(|m| m.is_present(SAVE_FLAG)),
+        http: http_options(&matches),
+        locale: matches.value_of(LOCALE_ARG).unwrap_or("en").to_string(),
+        profile: profile_name,
     };
 
     if matches.is_present(LOG_FLAG) {
@@ -134,18 +331,165 @@ fn main() {
     }
 
     if matches.subcommand_matches(SUB_COMMAND_SIGN_IN).is_some() {
-        signin_command(&command_config);
+        exit_on_error(signin_command(&command_config), command_config.silent);
     } else if matches.subcommand_matches(SUB_COMMAND_INFO).is_some() {
-        server_info(&command_config);
+        exit_on_error(server_info(&command_config), command_config.silent);
     } else if matches.subcommand_matches(SUB_COMMAND_CAPABILITIES).is_some() {
-        show_capabilities(&command_config);
+        exit_on_error(show_capabilities(&command_config), command_config.silent);
     } else if let Some(command_matches) = matches.subcommand_matches(SUB_COMMAND_CHECK) {
-        check(&command_config, &CheckCommandOpts {
-            files: command_matches.values_of(FILES_ARG).unwrap().map(String::from).collect(),
+        let output_format: OutputFormat = command_matches.value_of(OUTPUT_FORMAT_ARG).unwrap().parse().unwrap();
+        let json_errors = command_config.silent || output_format != OutputFormat::Text;
+        let fail_on = match command_matches.values_of(FAIL_ON_ARG) {
+            Some(values) => values.map(|v| v.parse().unwrap()).collect(),
+            None => exit_on_error(default_config.fail_on.iter()
+                                      .map(|v| v.parse().map_err(CommandError::Config))
+                                      .collect(), json_errors),
+        };
+        let check_result = check(&command_config, &CheckCommandOpts {
+            files: command_matches.values_of(FILES_ARG)
+                .map(|values| values.map(String::from).collect())
+                .unwrap_or_else(|| default_config.files.clone()),
+            include: command_matches.values_of(INCLUDE_ARG)
+                .map(|values| values.map(String::from).collect())
+                .unwrap_or_else(|| default_config.include.clone()),
+            exclude: default_config.exclude.iter().cloned()
+                .chain(command_matches.values_of(EXCLUDE_ARG).into_iter().flatten().map(String::from))
+                .collect(),
+            use_ignore_files: !command_matches.is_present(NO_IGNORE_FLAG),
+            staged_content: false,
+            git_changes: if command_matches.is_present(STAGED_FLAG) {
+                Some(GitChanges::Staged)
+            } else {
+                command_matches.value_of(CHANGED_SINCE_ARG).map(|commit| GitChanges::Since(commit.to_string()))
+            },
             guidance_profile: command_matches.value_of(GUIDANCE_PROFILE_ARG).map(String::from),
+            path_guidance_profiles: default_config.path_guidance_profiles.clone(),
+            content_format: command_matches.value_of(CONTENT_FORMAT_ARG).map(String::from),
+            path_content_formats: default_config.path_content_formats.clone(),
+            encoding: command_matches.value_of(ENCODING_ARG).map(String::from),
+            path_encodings: default_config.path_encodings.clone(),
             auth_links: command_matches.is_present(AUTH_LINKS_FLAG),
             max_concurrent: command_matches.value_of(MAX_CONCURRENT_ARG).unwrap().parse().unwrap(),
+            output_format,
+            quality_gate: QualityGate {
+                min_score: command_matches.value_of(MIN_SCORE_ARG).map(|s| s.parse().unwrap()),
+                fail_on,
+            },
+            junit_report: command_matches.value_of(JUNIT_REPORT_ARG).map(PathBuf::from),
+            sarif_report: command_matches.value_of(SARIF_REPORT_ARG).map(PathBuf::from),
+            show_issues: command_matches.is_present(SHOW_ISSUES_FLAG),
+            fix: if let Some(patch_file) = command_matches.value_of(FIX_PATCH_ARG) {
+                Some(FixMode::Patch(PathBuf::from(patch_file)))
+            } else if command_matches.is_present(FIX_FLAG) {
+                Some(FixMode::InPlace)
+            } else {
+                None
+            },
+            interactive: command_matches.is_present(INTERACTIVE_FLAG),
         });
+        let quality_gate_result = exit_on_error(check_result, json_errors);
+        process::exit(quality_gate_result.exit_code());
+    } else if let Some(hook_matches) = matches.subcommand_matches(SUB_COMMAND_HOOK) {
+        let quality_gate = QualityGate {
+            min_score: default_config.min_score,
+            fail_on: exit_on_error(default_config.fail_on.iter()
+                                       .map(|v| v.parse().map_err(CommandError::Config))
+                                       .collect(), command_config.silent),
+        };
+        if let Some(install_matches) = hook_matches.subcommand_matches(SUB_COMMAND_HOOK_INSTALL) {
+            // Access tokens are not written into the hook, so they must come from the config or the environment.
+            let mut global_args = vec![];
+            if matches.occurrences_of(SERVER_ADDRESS_ARG) > 0 {
+                global_args.push(format!("--{}={}", SERVER_ADDRESS_ARG, command_config.acrolinx_address));
+            }
+            // The default profile of the config is selected anyway.
+            if let Some(profile) = selected_profile_name() {
+                global_args.push(format!("--{}={}", PROFILE_ARG, profile));
+            }
+            exit_on_error(install_hook(&global_args, install_matches.is_present(FORCE_FLAG), &quality_gate),
+                          command_config.silent);
+        } else if hook_matches.subcommand_matches(SUB_COMMAND_HOOK_RUN).is_some() {
+            let check_result = check(&command_config, &CheckCommandOpts {
+                files: default_config.files.clone(),
+                include: default_config.include.clone(),
+                exclude: default_config.exclude.clone(),
+                use_ignore_files: true,
+                git_changes: Some(GitChanges::Staged),
+                staged_content: true,
+                guidance_profile: server_profile.guidance_profile.clone(),
+                path_guidance_profiles: default_config.path_guidance_profiles.clone(),
+                content_format: None,
+                path_content_formats: default_config.path_content_formats.clone(),
+                encoding: None,
+                path_encodings: default_config.path_encodings.clone(),
+                auth_links: false,
+                max_concurrent: server_profile.max_concurrent.unwrap_or(1),
+                output_format: OutputFormat::Text,
+                quality_gate,
+                junit_report: None,
+                sarif_report: None,
+                show_issues: true,
+                fix: None,
+                interactive: false,
+            });
+            let quality_gate_result = exit_on_error(check_result, command_config.silent);
+            process::exit(quality_gate_result.exit_code());
+        } else {
+            println!("{}", hook_matches.usage());
+        }
+    }
+}
+
+/// Prints the error and exits with the exit code of its category.
+fn exit_on_error<T>(result: Result<T, CommandError>, json: bool) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            error.print(json);
+            process::exit(error.exit_code());
+        }
+    }
+}
+
+/// The profile must be known before parsing the arguments, because it provides their defaults.
+fn selected_profile_name() -> Option<String> {
+    let long_arg = format!("--{}", PROFILE_ARG);
+    let long_arg_with_value = format!("--{}=", PROFILE_ARG);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == long_arg || arg == "-p" {
+            return args.next();
+        } else if arg.starts_with(&long_arg_with_value) {
+            return Some(arg[long_arg_with_value.len()..].to_string());
+        }
+    }
+    env::var(PROFILE_ENV_VAR.as_str()).ok()
+}
+
+fn http_options(matches: &ArgMatches) -> HttpOptions {
+    let seconds = |name: &str| matches.value_of(name).map(|s| Duration::from_secs(s.parse().unwrap()));
+    let default = HttpOptions::default();
+    HttpOptions {
+        connect_timeout: seconds(CONNECT_TIMEOUT_ARG).unwrap_or(default.connect_timeout),
+        request_timeout: seconds(TIMEOUT_ARG).unwrap_or(default.request_timeout),
+        retry: RetryPolicy {
+            max_attempts: matches.value_of(MAX_RETRIES_ARG)
+                .map(|s| s.parse::<u32>().unwrap().saturating_add(1))
+                .unwrap_or(default.retry.max_attempts),
+            ..default.retry.clone()
+        },
+        ..default
+    }
+}
+
+fn validate_seconds(value: String) -> Result<(), String> {
+    value.parse::<u64>().map(|_| ()).map_err(|_| format!("\"{}\" is no number of seconds", value))
+}
+
+fn validate_max_concurrent(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(max_concurrent) if max_concurrent > 0 => Ok(()),
+        _ => Err(format!("\"{}\" is no positive number", value)),
     }
 }
 